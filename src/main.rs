@@ -1,210 +1,1112 @@
-use std::{collections::HashMap, path::Path};
+use std::path::{Path, PathBuf};
 
-use clap::{Parser, Subcommand};
-use util::{log_analyzer::LogAnalyzer, log_data::LogData, models::*, parse_log, read_directory};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use clap::{
+    builder::PossibleValuesParser, error::ErrorKind, Args, CommandFactory, Parser, Subcommand,
+    ValueEnum,
+};
+use output::OutputFormat;
+use plot::{LineGraph, Panel, PlotConfig, Series};
+use table::{TableFormat, TableOptions};
+use util::{
+    backtest, count_users,
+    expression::Expression,
+    fit::{Criterion, FitMethod, GoodnessOfFit},
+    log_analyzer::{get_time_multiplier, Dimension, LogAnalyzer, TIME_RANGES},
+    log_data::{LogData, Metric},
+    models::*,
+    parse_log, read_log_files,
+    top::TopBy,
+    unique::UniqueCounter,
+};
 
+mod output;
 mod plot;
+mod report;
 mod table;
+mod tui;
 mod util;
 
 /// Handles Commands for specifying graphs to be generated
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
-    cmd: Commands,
-    time: String,
+    cmd: Option<Commands>,
+    /// Size of the buckets the logs are grouped into, every command but list-metrics needs it
+    time: Option<String>,
+    /// Writes the total and per bucket data to stdout, or the output file, in the given format
+    #[arg(long, value_enum, global = true)]
+    output: Option<OutputFormat>,
+    /// File the json, csv or ndjson output is written to instead of stdout, keeping it apart
+    /// from the fit results, tables and messages the command prints
+    #[arg(long, global = true)]
+    output_file: Option<PathBuf>,
+    /// File the html report is written to when the output is html
+    #[arg(long, global = true, default_value = "images/report.html", value_parser = report::parse_report_path)]
+    report: PathBuf,
+    /// File the graph is saved to, the format is inferred from the .png or .svg extension
+    #[arg(long, global = true, default_value = "images/2.8.png", value_parser = plot::parse_plot_path)]
+    out: PathBuf,
+    /// Width of the graph in pixels
+    #[arg(long, global = true, default_value_t = 1400, value_parser = clap::value_parser!(u32).range(1..))]
+    width: u32,
+    /// Height of the graph in pixels
+    #[arg(long, global = true, default_value_t = 1000, value_parser = clap::value_parser!(u32).range(1..))]
+    height: u32,
+    /// File the distinct users are kept in between runs, the total users counts the users of
+    /// this run along with every earlier run that used the same file
+    #[arg(long, global = true)]
+    users_file: Option<PathBuf>,
+    /// Draws the x-axis of line graphs on a logarithmic scale
+    #[arg(long, global = true)]
+    log_x: bool,
+    /// Draws the y-axis of line graphs on a logarithmic scale
+    #[arg(long, global = true)]
+    log_y: bool,
+}
+
+/// Reliability model fit to the errors and drawn along with the data
+#[derive(Args, Debug, Clone)]
+// Keeps the description above out of the help of the commands these are flattened into
+#[command(about = None, long_about = None)]
+struct ModelArgs {
+    /// Model fit to the errors, none leaves it out
+    #[arg(long, value_enum, default_value_t = ModelChoice::Schneidewind)]
+    model: ModelChoice,
+    /// How the parameters of the model are estimated
+    #[arg(long, value_enum, default_value_t = FitMethod::Mle)]
+    fit: FitMethod,
+    /// Statistic the models are ranked by when the model is auto
+    #[arg(long, value_enum, default_value_t = Criterion::Aic)]
+    criterion: Criterion,
+    /// Chance that the confidence intervals and band hold the true value, between 0 and 1
+    #[arg(long, default_value_t = 0.95, value_parser = parse_confidence)]
+    confidence: f64,
+    /// Unit of time the model is fit in and gives the failure intensity per, the bucket size
+    /// by default
+    #[arg(long, value_parser = PossibleValuesParser::new(TIME_RANGES.map(|x| x.0)))]
+    unit: Option<String>,
+    /// What the model measures time in, the x-axis of cumulative graphs when it can be and
+    /// elapsed time otherwise
+    #[arg(long, value_enum)]
+    exposure: Option<Exposure>,
+}
+
+impl ModelArgs {
+    /// Exposure the model is fit against, elapsed time unless another was chosen
+    fn get_exposure(&self) -> Exposure {
+        self.exposure.unwrap_or(Exposure::Time)
+    }
+
+    /// Name of the unit the model measures exposure in along with the length in seconds of the
+    /// unit of time, given the bucket size
+    fn get_unit<'a>(&'a self, bucket: &'a str) -> (&'a str, f64) {
+        let unit = self.unit.as_deref().unwrap_or(bucket);
+        let name = self.get_exposure().get_unit().unwrap_or(unit);
+        (name, get_time_multiplier(unit).unwrap() as f64)
+    }
+
+    /// Model chosen to fit the buckets, ranking every model first when it is picked
+    /// automatically, or none when the model is left out or none of them can be fit
+    fn get_model(
+        &self,
+        log_data_by_time: &[LogData],
+        total_log_data: &LogData,
+        bucket: &str,
+    ) -> Option<Models> {
+        let (_, unit_seconds) = self.get_unit(bucket);
+        let get_model = |model| Models {
+            model,
+            fit: self.fit,
+            total_errors: total_log_data.errors as f64,
+            log_data: log_data_by_time.to_vec(),
+            intervals: self
+                .get_exposure()
+                .get_intervals(log_data_by_time, unit_seconds),
+            confidence: self.confidence,
+        };
+        let models = self.model.get_models();
+        let name = match models.len() {
+            0 => return None,
+            1 => models[0],
+            _ => {
+                let mut ranked = Vec::new();
+                for model in models {
+                    let model = get_model(model);
+                    match model.fit_params() {
+                        Ok(fit) => {
+                            ranked.push((model.model, model.get_goodness_of_fit(&fit.params)))
+                        }
+                        Err(error) => eprintln!("Unable to fit the {} model: {error}", model.model),
+                    }
+                }
+                ranked.sort_by(|a, b| {
+                    let score = |x: &GoodnessOfFit| self.criterion.get_score(x);
+                    score(&a.1).total_cmp(&score(&b.1))
+                });
+                println!(
+                    "Models ranked by {}\n{}\n",
+                    self.criterion.to_possible_value().unwrap().get_name(),
+                    table::get_model_table(&ranked)
+                );
+                ranked.first()?.0
+            }
+        };
+        Some(get_model(name))
+    }
+
+    /// Expected failures by the start of each bucket, expected failures within each bucket and
+    /// confidence band of the model fit to the buckets, or none when the model is left out or
+    /// can't be fit
+    fn get_curves(
+        &self,
+        log_data_by_time: &[LogData],
+        total_log_data: &LogData,
+        bucket: &str,
+    ) -> Option<(Curve, Curve, Band)> {
+        let model = self.get_model(log_data_by_time, total_log_data, bucket)?;
+        let name = model.model;
+        match model.get_curve() {
+            Ok(curves) => Some(curves),
+            Err(error) => {
+                eprintln!("Unable to fit the {name} model: {error}");
+                None
+            }
+        }
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
 enum Commands {
-    By { x_axis: String, y_axis: String },
-    Cumulative { x_axis: String, y_axis: String },
-    Ratio { x_axis: String, y_axis: String },
-    CumulativeRatio { y_axis: String },
+    By {
+        #[arg(value_parser = Expression::parse)]
+        x_axis: Expression,
+        /// Comma separated metrics or expressions drawn against the left y-axis
+        #[arg(value_parser = Expression::parse, value_delimiter = ',')]
+        y_axis: Vec<Expression>,
+        /// Comma separated metrics or expressions drawn against a second y-axis on the right
+        #[arg(long, value_parser = Expression::parse, value_delimiter = ',')]
+        secondary: Vec<Expression>,
+        /// Draws every metric once for each group of these comma separated dimensions
+        #[arg(long, value_enum, value_delimiter = ',')]
+        group_by: Vec<Dimension>,
+        /// Number of groups drawn, largest first
+        #[arg(long, default_value_t = 5)]
+        groups: usize,
+        #[command(flatten)]
+        model: ModelArgs,
+    },
+    Cumulative {
+        #[arg(value_parser = Expression::parse)]
+        x_axis: Expression,
+        #[arg(value_parser = Expression::parse)]
+        y_axis: Expression,
+        #[command(flatten)]
+        model: ModelArgs,
+    },
+    Ratio {
+        #[arg(value_parser = Expression::parse)]
+        x_axis: Expression,
+        #[arg(value_parser = Expression::parse)]
+        y_axis: Expression,
+    },
+    CumulativeRatio {
+        #[arg(value_parser = Expression::parse)]
+        y_axis: Expression,
+        #[command(flatten)]
+        model: ModelArgs,
+    },
+    /// Fits the models to the earlier buckets and measures how well they predict the later ones
+    Backtest {
+        /// Percent of the buckets the models are first fit to, the rest are predicted
+        #[arg(long, default_value_t = 70.0)]
+        train: f64,
+        /// Refits after every --step buckets past the training share, predicting the next
+        /// --horizon buckets each time, instead of predicting every remaining bucket at once
+        #[arg(long)]
+        rolling: bool,
+        /// Buckets predicted from each origin of a rolling backtest
+        #[arg(long, default_value_t = 1)]
+        horizon: usize,
+        /// Buckets between the origins of a rolling backtest
+        #[arg(long, default_value_t = 1)]
+        step: usize,
+        /// Models compared, auto compares every model
+        #[arg(long, value_enum, default_value_t = ModelChoice::Auto)]
+        model: ModelChoice,
+        /// How the parameters of the models are estimated at each origin
+        #[arg(long, value_enum, default_value_t = FitMethod::Mle)]
+        fit: FitMethod,
+        /// Unit of time the models are fit in, the bucket size by default
+        #[arg(long, value_parser = PossibleValuesParser::new(TIME_RANGES.map(|x| x.0)))]
+        unit: Option<String>,
+        /// What the models measure time in
+        #[arg(long, value_enum, default_value_t = Exposure::Time)]
+        exposure: Exposure,
+        #[arg(long, value_enum, default_value_t = TableFormat::Terminal)]
+        format: TableFormat,
+    },
+    /// Predicts the errors still to come and how reliable the coming buckets are from the model
+    Forecast {
+        /// Number of buckets past the last one to predict the errors of
+        #[arg(short, long, default_value_t = 10)]
+        periods: usize,
+        /// Length of the window the chance of running without errors is given for, in the unit
+        /// the model measures exposure in
+        #[arg(long, default_value_t = 1.0)]
+        mission: f64,
+        /// Errors per unit of exposure to estimate the time until the failure intensity falls to
+        #[arg(long)]
+        target_intensity: Option<f64>,
+        #[arg(long, value_enum, default_value_t = TableFormat::Terminal)]
+        format: TableFormat,
+        #[command(flatten)]
+        model: ModelArgs,
+    },
+    /// Lists the metrics that can be used as axes and in expressions along with their units
+    ListMetrics,
+    /// Draws traffic, errors, bytes, users, the reliability model and top endpoints on one image
+    Dashboard {
+        /// Number of panels in each row
+        #[arg(long, default_value_t = 3)]
+        columns: usize,
+    },
+    /// Draws a histogram of the response sizes
+    Histogram {
+        /// Number of equally sized ranges the sizes are split into
+        #[arg(long, default_value_t = 30)]
+        bins: usize,
+    },
+    /// Draws the most common values of a field as bars
+    Bars {
+        #[arg(value_enum)]
+        field: Dimension,
+        /// Number of values to show
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Draws the traffic by day of the week and hour of the day
+    Heatmap,
+    /// Opens a terminal dashboard for switching bucket size, metric, filters and time window
+    Tui,
+    /// Prints the values of a dimension with the most hits, bytes or errors
+    Top {
+        #[arg(value_enum)]
+        field: Dimension,
+        #[arg(long, value_enum, default_value_t = TopBy::Hits)]
+        by: TopBy,
+        /// Number of values to show
+        #[arg(short, long, default_value_t = 10)]
+        count: usize,
+        /// Only counts logs from this date or time on, such as 2024-03-12 or 2024-03-12T06:00:00Z
+        #[arg(long, value_parser = parse_date)]
+        since: Option<DateTime<Utc>>,
+        /// Only counts logs before this date or time
+        #[arg(long, value_parser = parse_date)]
+        until: Option<DateTime<Utc>>,
+        /// Most values counted at once, counts become estimates with an error bound past it
+        #[arg(long, default_value_t = 100_000)]
+        capacity: usize,
+        #[arg(long, value_enum, default_value_t = TableFormat::Terminal)]
+        format: TableFormat,
+    },
+    /// Prints the totals of the logs grouped by one or more dimensions as a table
+    Groups {
+        /// Comma separated dimensions the logs are grouped by
+        #[arg(value_enum, value_delimiter = ',', required = true)]
+        dimensions: Vec<Dimension>,
+        /// Number of groups to show, largest first
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// Comma separated columns to show, either start_time, end_time or a metric expression
+        #[arg(long, value_delimiter = ',', value_parser = table::parse_column, default_value = "hits,errors,users,sessions,total_bytes,avg_bytes")]
+        columns: Vec<String>,
+        /// Column to sort the rows by
+        #[arg(long, value_parser = table::parse_column)]
+        sort: Option<String>,
+        /// Sorts the rows in descending order
+        #[arg(long)]
+        desc: bool,
+        /// Adds a row with the totals over all groups
+        #[arg(long)]
+        totals: bool,
+        /// Shows bytes, durations and counts in human readable units
+        #[arg(long)]
+        human: bool,
+        #[arg(long, value_enum, default_value_t = TableFormat::Terminal)]
+        format: TableFormat,
+    },
+    /// Prints the per bucket data as a table
+    Table {
+        /// Comma separated columns to show, either start_time, end_time or a metric expression
+        #[arg(long, value_delimiter = ',', value_parser = table::parse_column, default_value = "start_time,hits,errors,users,sessions,total_bytes,avg_bytes,atbl,atbe")]
+        columns: Vec<String>,
+        /// Column to sort the rows by
+        #[arg(long, value_parser = table::parse_column)]
+        sort: Option<String>,
+        /// Sorts the rows in descending order
+        #[arg(long)]
+        desc: bool,
+        /// Adds a row with the totals over all buckets
+        #[arg(long)]
+        totals: bool,
+        /// Shows bytes, durations and counts in human readable units
+        #[arg(long)]
+        human: bool,
+        #[arg(long, value_enum, default_value_t = TableFormat::Terminal)]
+        format: TableFormat,
+    },
+}
+
+/// Reads a UTC date, a UTC date and time or an RFC 3339 time
+fn parse_date(date: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(date) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(date, "%Y-%m-%dT%H:%M:%S") {
+        return Ok(time.and_utc());
+    }
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(day) => Ok(day.and_hms_opt(0, 0, 0).unwrap().and_utc()),
+        Err(_) => Err("expected a date such as 2024-03-12 or 2024-03-12T06:00:00Z".to_string()),
+    }
+}
+
+/// Reads a confidence level strictly between 0 and 1
+fn parse_confidence(level: &str) -> Result<f64, String> {
+    match level.parse::<f64>() {
+        Ok(level) if level > 0.0 && level < 1.0 => Ok(level),
+        _ => Err("expected a level between 0 and 1 such as 0.95".to_string()),
+    }
+}
+
+/// Unit of time such as hour for a count of one and hours otherwise
+fn plural(count: f64, unit: &str) -> String {
+    match count == 1.0 {
+        true => unit.to_string(),
+        false => format!("{unit}s"),
+    }
+}
+
+/// Label of x-axes that show the start of each bucket as a date
+const TIME_AXIS: &str = "time (UTC)";
+
+fn is_time_axis(x_axis: &Expression) -> bool {
+    x_axis.get_metric() == Some(Metric::Time)
+}
+
+fn get_x_axis_label(x_axis: &Expression) -> String {
+    match is_time_axis(x_axis) {
+        true => TIME_AXIS.to_string(),
+        false => x_axis.to_string(),
+    }
+}
+
+/// Point of a bucket with time axes read as dates, `None` when either axis divides by zero
+fn get_point(data: &LogData, x_axis: &Expression, y_axis: &Expression) -> Option<(f64, f64)> {
+    Some((get_x(data, x_axis)?, y_axis.evaluate(data)?))
+}
+
+/// Where a bucket is drawn on the x-axis, none when the x-axis divides by zero for it
+fn get_x(data: &LogData, x_axis: &Expression) -> Option<f64> {
+    match is_time_axis(x_axis) {
+        true => Some(data.get_timestamp()),
+        false => x_axis.evaluate(data),
+    }
+}
+
+/// Tells the user about buckets left out of a graph instead of drawing them as infinite
+fn warn_skipped(skipped: usize, total: usize, name: &str) {
+    if skipped > 0 {
+        eprintln!("Skipped {skipped} of {total} buckets of {name} because of a division by zero");
+    }
+}
+
+/// Names, units and descriptions of every metric as aligned columns
+fn get_metric_list() -> String {
+    let rows: Vec<[String; 3]> = Metric::value_variants()
+        .iter()
+        .map(|metric| {
+            [
+                metric.to_string(),
+                metric.get_unit().to_string(),
+                metric.get_description(),
+            ]
+        })
+        .collect();
+    let name_width = rows.iter().map(|x| x[0].len()).max().unwrap_or(0);
+    let unit_width = rows.iter().map(|x| x[1].len()).max().unwrap_or(0);
+    rows.iter()
+        .map(|[name, unit, description]| {
+            format!("{name:<name_width$}  {unit:<unit_width$}  {description}")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+        + "\n\nMetrics can be combined with + - * / and parentheses, such as errors/hits*100"
+}
+
+/// Cumulative values of the y-axis metric along with the curve of the model fit to the errors
+/// and its confidence band
+fn get_cumulative_points(
+    log_data_by_time: &[LogData],
+    total_log_data: &LogData,
+    x_axis: &Expression,
+    y_axis: &Expression,
+    model: &ModelArgs,
+    bucket: &str,
+) -> (Curve, Option<(Curve, Band)>) {
+    let mut data_point: Curve = Vec::new();
+    let mut count = [0.0, 0.0];
+    let mut second_point = model
+        .get_curves(log_data_by_time, total_log_data, bucket)
+        .map(|(curve, _, band)| (curve, band));
+    let mut skipped = 0;
+    for (index, data) in log_data_by_time.iter().enumerate() {
+        match is_time_axis(x_axis) {
+            true => {
+                let current_x = data.get_timestamp();
+                if let Some((second_point, band)) = &mut second_point {
+                    second_point[index].0 = current_x;
+                    if let Some(bound) = band.get_mut(index) {
+                        bound.0 = current_x;
+                    }
+                }
+                let Some(current_y) = y_axis.evaluate(data) else {
+                    skipped += 1;
+                    continue;
+                };
+                data_point.push((current_x, count[1]));
+                count[1] += current_y;
+            }
+            false => {
+                if let Some((second_point, band)) = &mut second_point {
+                    second_point[index].0 = count[0];
+                    if let Some(bound) = band.get_mut(index) {
+                        bound.0 = count[0];
+                    }
+                }
+                let Some(current_x) = x_axis.evaluate(data) else {
+                    skipped += 1;
+                    continue;
+                };
+                // The usage of a bucket is still added when its y value can't be drawn, so the
+                // later buckets stay where the model's exposure puts them
+                let start_x = count[0];
+                count[0] += current_x;
+                let Some(current_y) = y_axis.evaluate(data) else {
+                    skipped += 1;
+                    continue;
+                };
+                data_point.push((start_x, count[1]));
+                count[1] += current_y;
+            }
+        }
+    }
+    warn_skipped(skipped, log_data_by_time.len(), &y_axis.to_string());
+    (data_point, second_point)
+}
+
+/// Panels for an overview of the traffic, errors and reliability of the logs
+fn get_dashboard_panels(
+    log_analyzer: &LogAnalyzer,
+    log_data_by_time: &[LogData],
+    total_log_data: &LogData,
+    bucket: &str,
+) -> Vec<Panel> {
+    let get_points = |metric: &dyn Fn(&LogData) -> f64| -> Curve {
+        log_data_by_time
+            .iter()
+            .map(|data| (data.get_timestamp(), metric(data)))
+            .collect()
+    };
+    let line_panel = |title: &str, y_axis: &str, series: Vec<Series>| {
+        Panel::Lines(LineGraph {
+            title: title.to_string(),
+            x_axis: TIME_AXIS.to_string(),
+            y_axis: y_axis.to_string(),
+            time_axis: true,
+            log_x: false,
+            log_y: false,
+            series,
+        })
+    };
+    let single = |name: &str, points: Curve| vec![Series::new(name, points)];
+    let (errors, model) = get_cumulative_points(
+        log_data_by_time,
+        total_log_data,
+        &Metric::Time.into(),
+        &Metric::Errors.into(),
+        &ModelArgs {
+            model: ModelChoice::Schneidewind,
+            fit: FitMethod::Mle,
+            criterion: Criterion::Aic,
+            confidence: 0.95,
+            unit: None,
+            exposure: None,
+        },
+        bucket,
+    );
+    vec![
+        line_panel(
+            "Traffic",
+            "hits",
+            single("hits", get_points(&|data| data.log_count as f64)),
+        ),
+        line_panel(
+            "Error Rate",
+            "errors per 100 hits",
+            single(
+                "errors per 100 hits",
+                get_points(&|data| data.errors as f64 / data.log_count as f64 * 100.0),
+            ),
+        ),
+        line_panel(
+            "Bytes",
+            "total_bytes",
+            single("total_bytes", get_points(&|data| data.total_bytes)),
+        ),
+        line_panel(
+            "Unique Users",
+            "users",
+            single("users", get_points(&|data| data.users as f64)),
+        ),
+        line_panel(
+            "Reliability Model",
+            "cumulative errors",
+            [Series::new("errors", errors)]
+                .into_iter()
+                .chain(model.map(|(model, band)| Series::new("model", model).with_band(band)))
+                .collect(),
+        ),
+        Panel::Bars {
+            title: "Top Endpoints".to_string(),
+            x_axis: "hits".to_string(),
+            bars: log_analyzer.get_top_counts(Dimension::Path, 10),
+        },
+    ]
 }
 
 fn main() {
     let args = Cli::parse();
-    let time = args.time.trim();
+    if let Some(Commands::ListMetrics) = args.cmd {
+        println!("{}", get_metric_list());
+        return;
+    }
+    let Some(time) = args.time.as_deref().map(str::trim) else {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "the bucket size <TIME> is required",
+            )
+            .exit();
+    };
     let log_file = Path::new("logs/");
-    let log_contents = read_directory(log_file);
-    let time_multiplier: HashMap<String, i64> = HashMap::from([
-        ("year".to_string(), 31556952),
-        ("month".to_string(), 2629800),
-        ("day".to_string(), 86400),
-        ("hour".to_string(), 3600),
-        ("min".to_string(), 60),
-        ("sec".to_string(), 1),
-    ]);
+    let log_files = read_log_files(log_file);
     // Puts all logs in a vector tracking each part and removing redundant logs.
-    let log_data = parse_log(log_contents);
-    let log_data = LogAnalyzer {
+    let log_data = parse_log(log_files);
+    if log_data.is_empty() {
+        eprintln!("No logs could be parsed from {}", log_file.display());
+        std::process::exit(1);
+    }
+    let log_analyzer = LogAnalyzer {
         logs: log_data,
-        time_multi: *time_multiplier.get(time).unwrap(),
+        time_multi: get_time_multiplier(time).unwrap(),
+    };
+    let (mut log_data_by_time, mut total_log_data) = log_analyzer.clone().get_data(time);
+    log_data_by_time.sort_by_key(|x| x.start_time);
+    if let Some(path) = &args.users_file {
+        // Counting a user again is harmless so the same logs can be read by several runs
+        let mut users = UniqueCounter::load(path);
+        users.merge(&count_users(&log_analyzer.logs));
+        users.save(path);
+        total_log_data.users = users.count();
+    }
+    match args.output {
+        Some(OutputFormat::Html) => {
+            let panels =
+                get_dashboard_panels(&log_analyzer, &log_data_by_time, &total_log_data, time);
+            let report = report::get_report(time, &total_log_data, &log_data_by_time, &panels);
+            report::save_report(&args.report, &report);
+        }
+        Some(format) => {
+            let data = output::format_data(format, &total_log_data, &log_data_by_time);
+            match &args.output_file {
+                Some(path) => output::save_data(path, &data),
+                None => println!("{}", data),
+            }
+        }
+        // The table already holds the totals
+        None if !matches!(
+            args.cmd,
+            Some(
+                Commands::Table { .. }
+                    | Commands::Groups { .. }
+                    | Commands::Top { .. }
+                    | Commands::Forecast { .. }
+                    | Commands::Backtest { .. }
+                    | Commands::Tui
+            )
+        ) =>
+        {
+            println!("{}", total_log_data)
+        }
+        None => {}
+    }
+    let Some(cmd) = args.cmd else {
+        return;
+    };
+    let plot_config = PlotConfig {
+        path: args.out,
+        width: args.width,
+        height: args.height,
     };
-    let (mut log_data_by_time, total_log_data) = log_data.get_data(time);
-    log_data_by_time.sort_by(|a, b| (a.time as i64).cmp(&(b.time as i64).clone()));
-    println!("{}", total_log_data);
     let mut data_point: Vec<(f64, f64)> = Vec::new();
     //println!("{}", time);
-    match args.cmd {
-        Commands::By { mut x_axis, y_axis } => {
-            let length = &log_data_by_time.len();
-            let model = Models {
-                model: MODELTYPES::SCWIND,
-                total_errors: total_log_data.errors as f64,
-                log_data: log_data_by_time.clone(),
-                data_point: *length,
+    match cmd {
+        Commands::By {
+            x_axis,
+            y_axis,
+            secondary,
+            group_by,
+            groups,
+            model,
+        } => {
+            let time_axis = is_time_axis(&x_axis);
+            // Without dimensions all of the logs are drawn as a single unnamed group
+            let groups: Vec<(String, Vec<LogData>)> = match group_by.is_empty() {
+                true => vec![(String::new(), log_data_by_time.clone())],
+                false => log_analyzer
+                    .get_group_data(&group_by, time)
+                    .into_iter()
+                    .take(groups)
+                    .map(|(group, data, _)| (group, data))
+                    .collect(),
             };
-            let (_, second_prime) = model.get_curve();
+            let mut series: Vec<Series> = Vec::new();
             // Move to different file later
-            match x_axis.trim() {
-                "time" => {
-                    let mut index = 0.0;
-                    for data in log_data_by_time {
-                        //println!("{:#?}\n", data);
-                        let (_, y) = data.get_data_point(&x_axis, &y_axis);
-
-                        //println!("{:?}", (x, y));
-                        data_point.push((index, y));
-                        index += 1.0
-                    }
-
-                    x_axis = time.to_string();
-                }
-                _ => {
-                    for data in log_data_by_time {
-                        //println!("{:#?}\n", data);
-                        let (x, y) = data.get_data_point(&x_axis, &y_axis);
-
-                        //println!("{:?}", (x, y));
-                        data_point.push((x, y));
+            for (group, buckets) in &groups {
+                for metric in y_axis.iter().chain(&secondary) {
+                    let name = match group.is_empty() {
+                        true => metric.to_string(),
+                        false => format!("{group} {metric}"),
+                    };
+                    let points: Vec<Option<(f64, f64)>> = buckets
+                        .iter()
+                        .map(|data| get_point(data, &x_axis, metric))
+                        .collect();
+                    let skipped = points.iter().filter(|x| x.is_none()).count();
+                    warn_skipped(skipped, points.len(), &name);
+                    let data_point: Curve = points.into_iter().flatten().collect();
+                    match secondary.contains(metric) {
+                        true => series.push(Series::secondary(&name, data_point)),
+                        false => series.push(Series::new(&name, data_point)),
                     }
                 }
             }
-            plot::plot_double_graph(
-                data_point[0].0,
-                data_point.last().unwrap().0,
-                y_axis.to_owned(),
-                x_axis.to_owned(),
-                data_point,
-                second_prime,
+            // The model is fit to the errors of every log so it is left out of grouped graphs
+            let curves = match group_by.is_empty() {
+                true => model.get_curves(&log_data_by_time, &total_log_data, time),
+                false => None,
+            };
+            if let Some((_, increments, _)) = curves {
+                // The expected errors of each bucket are drawn where that bucket's data is
+                let second_point: Curve = increments
+                    .iter()
+                    .zip(&log_data_by_time)
+                    .filter_map(|(point, data)| Some((get_x(data, &x_axis)?, point.1)))
+                    .collect();
+                series.push(Series::new("model", second_point));
+            }
+            plot::plot_series(
+                &plot_config,
+                LineGraph {
+                    title: "Log Data".to_string(),
+                    x_axis: get_x_axis_label(&x_axis),
+                    y_axis: y_axis
+                        .iter()
+                        .map(|x| x.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                    time_axis,
+                    log_x: args.log_x,
+                    log_y: args.log_y,
+                    series,
+                },
             );
         }
 
-        Commands::Cumulative { mut x_axis, y_axis } => {
-            let mut count = vec![0.0, 0.0];
-            let length = &log_data_by_time.len();
-            let model = Models {
-                model: MODELTYPES::SCWIND,
-                total_errors: total_log_data.errors as f64,
-                log_data: log_data_by_time.clone(),
-                data_point: *length,
+        Commands::Cumulative {
+            x_axis,
+            y_axis,
+            mut model,
+        } => {
+            // Fits the model against the usage drawn on the x-axis so both are in the same space
+            if model.exposure.is_none() {
+                model.exposure = x_axis.get_metric().and_then(Exposure::from_metric);
+            }
+            let (data_point, second_point) = get_cumulative_points(
+                &log_data_by_time,
+                &total_log_data,
+                &x_axis,
+                &y_axis,
+                &model,
+                time,
+            );
+            let mut series = vec![Series::new(&y_axis.to_string(), data_point)];
+            if let Some((second_point, band)) = second_point {
+                series.push(Series::new("model", second_point).with_band(band));
+            }
+            plot::plot_series(
+                &plot_config,
+                LineGraph {
+                    title: "Log Data".to_string(),
+                    x_axis: get_x_axis_label(&x_axis),
+                    y_axis: format!("cumulative {y_axis}"),
+                    time_axis: is_time_axis(&x_axis),
+                    log_x: args.log_x,
+                    log_y: args.log_y,
+                    series,
+                },
+            );
+        }
+        Commands::Ratio { x_axis, y_axis } => {
+            let time_axis = is_time_axis(&x_axis);
+            let y_axis = match y_axis.get_metric() {
+                Some(_) => format!("errors/{y_axis}"),
+                None => format!("errors/({y_axis})"),
             };
-            let (mut second_point, _) = model.get_curve();
-            let mut index = 0 as usize;
+            let ratio = Expression::parse(&y_axis).unwrap();
+            // Move to different file later
             for data in &log_data_by_time {
-                match x_axis.trim() {
-                    "time" => {
-                        let (current_x, current_y) =
-                            <LogData as Clone>::clone(&data).get_data_point("time", &y_axis);
-                        second_point[index].0 = current_x;
-                        data_point.push((current_x, count[1] as f64));
-                        index += 1;
-                        count[1] += current_y;
-                    }
-                    _ => {
-                        let (current_x, current_y) =
-                            <LogData as Clone>::clone(&data).get_data_point(&x_axis, &y_axis);
-                        data_point.push((count[0], count[1] as f64));
-                        second_point[index].0 = count[0];
-                        count[0] += current_x;
-                        count[1] += current_y;
-                        index += 1;
-                    }
+                //println!("{:#?}\n", data);
+                if let Some(point) = get_point(data, &x_axis, &ratio) {
+                    data_point.push(point);
                 }
             }
-            if x_axis.trim() == "time" {
-                x_axis = time.to_string();
-            }
-            table::get_line_similarity(&data_point, &second_point);
-            plot::plot_double_graph(
-                data_point[0].0,
-                data_point.last().unwrap().0,
-                y_axis.to_owned(),
-                x_axis.to_owned(),
-                data_point,
-                second_point,
+            warn_skipped(
+                log_data_by_time.len() - data_point.len(),
+                log_data_by_time.len(),
+                &y_axis,
+            );
+            plot::plot_series(
+                &plot_config,
+                LineGraph {
+                    title: "Log Data".to_string(),
+                    x_axis: get_x_axis_label(&x_axis),
+                    y_axis: y_axis.clone(),
+                    time_axis,
+                    log_x: args.log_x,
+                    log_y: args.log_y,
+                    series: vec![Series::new(&y_axis, data_point)],
+                },
             );
         }
-        Commands::Ratio { mut x_axis, y_axis } => {
-            // Move to different file later
-            for data in log_data_by_time {
-                //println!("{:#?}\n", data);
-                let (x, mut y) = data.clone().get_data_point(&x_axis, &y_axis);
-                y = data.get_data("errors") / y;
-
-                //println!("{:?}", (x, y));
-                data_point.push((x, y));
+        Commands::Dashboard { columns } => {
+            let panels =
+                get_dashboard_panels(&log_analyzer, &log_data_by_time, &total_log_data, time);
+            plot::plot_dashboard(&plot_config, columns.max(1), panels);
+        }
+        Commands::Histogram { bins } => {
+            let sizes: Vec<f64> = log_analyzer.logs.iter().map(|log| log.size).collect();
+            plot::plot_histogram(
+                &plot_config,
+                "Response Sizes",
+                "response size (kB)",
+                sizes,
+                bins.max(1),
+            );
+        }
+        Commands::Bars { field, top } => {
+            let bars = log_analyzer.get_top_counts(field, top);
+            let title = match field {
+                Dimension::Ip => "Top IPs",
+                Dimension::Subnet => "Top Subnets",
+                Dimension::User => "Top Users",
+                Dimension::Status => "Top Status Codes",
+                Dimension::Method => "Top Methods",
+                Dimension::Path => "Top Endpoints",
+                Dimension::Agent => "Top User Agent Families",
+                Dimension::UserAgent => "Top User Agents",
+                Dimension::Referer => "Top Referers",
+                Dimension::Source => "Top Sources",
+            };
+            plot::plot_bars(&plot_config, title, "hits", bars);
+        }
+        Commands::Heatmap => {
+            plot::plot_heatmap(
+                &plot_config,
+                "Weekly Traffic",
+                log_analyzer.get_weekly_traffic(),
+            );
+        }
+        Commands::ListMetrics => unreachable!("metrics are listed before the logs are read"),
+        Commands::Tui => {
+            tui::run(log_analyzer.logs, time).expect("unable to run the terminal dashboard");
+        }
+        Commands::Top {
+            field,
+            by,
+            count,
+            since,
+            until,
+            capacity,
+            format,
+        } => {
+            let counts = log_analyzer
+                .get_window(since, until)
+                .get_heavy_hitters(field, by, capacity);
+            if counts.total == 0 {
+                println!(
+                    "No {} in the selected window",
+                    by.to_possible_value().unwrap().get_name()
+                );
+                return;
             }
-            let y_axis = format!("errors/{y_axis}");
-            match x_axis.trim() {
-                "time" => {
-                    x_axis = time.to_string();
-                    plot::plot_graph(
-                        data_point[0].0,
-                        data_point.last().unwrap().0,
-                        y_axis.to_owned(),
-                        x_axis.to_owned(),
-                        data_point,
-                    );
-                }
-                _ => {
-                    plot::plot_graph(
-                        data_point[0].0,
-                        data_point.last().unwrap().0,
-                        y_axis.to_owned(),
-                        x_axis.to_owned(),
-                        data_point,
-                    );
-                }
+            println!(
+                "{}",
+                table::get_top_table(format, field, by, &counts, count)
+            );
+            if counts.approximate {
+                println!(
+                    "\nMore than {capacity} values were seen so counts are estimates that are at most max_error too high"
+                );
             }
         }
-        Commands::CumulativeRatio { y_axis } => {
-            let length = &log_data_by_time.len();
-            let mut count = vec![0.0, 0.0];
-            let model = Models {
-                model: MODELTYPES::SCWIND,
-                total_errors: total_log_data.errors as f64,
-                log_data: log_data_by_time.clone(),
-                data_point: *length,
+        Commands::Groups {
+            dimensions,
+            top,
+            columns,
+            sort,
+            desc,
+            totals,
+            human,
+            format,
+        } => {
+            let options = TableOptions {
+                columns,
+                sort,
+                descending: desc,
+                totals,
+                human,
+                format,
             };
-            let (mut second_point, _) = model.get_curve();
-            let mut index = 0;
+            let groups: Vec<(String, LogData)> = log_analyzer
+                .get_group_data(&dimensions, time)
+                .into_iter()
+                .take(top)
+                .map(|(group, _, total)| (group, total))
+                .collect();
+            println!(
+                "{}",
+                table::get_group_table(&options, &groups, &total_log_data)
+            );
+        }
+        Commands::Table {
+            columns,
+            sort,
+            desc,
+            totals,
+            human,
+            format,
+        } => {
+            let options = TableOptions {
+                columns,
+                sort,
+                descending: desc,
+                totals,
+                human,
+                format,
+            };
+            println!(
+                "{}",
+                table::get_table(&options, &log_data_by_time, &total_log_data)
+            );
+        }
+        Commands::CumulativeRatio { y_axis, model } => {
+            let mut count = [0.0, 0.0];
+            let model_point = model
+                .get_curves(&log_data_by_time, &total_log_data, time)
+                .map(|(curve, _, _)| curve);
+            let mut second_point: Curve = Vec::new();
             // Move to different file later
-            for data in &log_data_by_time {
+            for (index, data) in log_data_by_time.iter().enumerate() {
                 //println!("{:#?}\n", data);
-                let (current_x, current_y) = data.clone().get_data_point("time", &y_axis);
-                let y = <LogData as Clone>::clone(&data).get_data("errors");
-                second_point[index].0 = current_x;
-                second_point[index].1 = second_point[index].1 / current_y;
-
-                data_point.push((current_x, count[1] / current_y as f64));
-                index += 1;
+                let current_x = data.get_timestamp();
+                let y = <LogData as Clone>::clone(data).get_data(Metric::Errors);
+                // Buckets dividing by zero still add their errors to the count
+                match y_axis.evaluate(data) {
+                    Some(current_y) if current_y != 0.0 => {
+                        if let Some(model_point) = &model_point {
+                            second_point.push((current_x, model_point[index].1 / current_y));
+                        }
+                        data_point.push((current_x, count[1] / current_y));
+                    }
+                    _ => {}
+                }
                 count[1] += y;
 
                 //println!("{:?}", (x, y));
             }
+            warn_skipped(
+                log_data_by_time.len() - data_point.len(),
+                log_data_by_time.len(),
+                &format!("errors/{y_axis}"),
+            );
             let y_axis = format!("errors/{y_axis}");
-            //table::get_line_similarity(&data_point, &second_point);
-            plot::plot_double_graph(
-                data_point[0].0,
-                data_point.last().unwrap().0,
-                y_axis.to_owned(),
-                time.to_string(),
-                data_point,
-                second_point,
+            plot::plot_series(
+                &plot_config,
+                LineGraph {
+                    title: "Log Data".to_string(),
+                    x_axis: TIME_AXIS.to_string(),
+                    y_axis: format!("cumulative {y_axis}"),
+                    time_axis: true,
+                    log_x: args.log_x,
+                    log_y: args.log_y,
+                    series: match model_point {
+                        Some(_) => vec![
+                            Series::new(&y_axis, data_point),
+                            Series::new("model", second_point),
+                        ],
+                        None => vec![Series::new(&y_axis, data_point)],
+                    },
+                },
+            );
+        }
+        Commands::Forecast {
+            periods,
+            mission,
+            target_intensity,
+            format,
+            model,
+        } => {
+            let (unit, unit_seconds) = model.get_unit(time);
+            let exposure = model.get_exposure();
+            let Some(model) = model.get_model(&log_data_by_time, &total_log_data, time) else {
+                eprintln!("No model to forecast with");
+                return;
+            };
+            let name = model.model;
+            let fitted = match model.get_fitted_model() {
+                Ok(fitted) => fitted,
+                Err(error) => {
+                    eprintln!("Unable to fit the {name} model: {error}");
+                    return;
+                }
+            };
+            let end = model.get_times().last().copied().unwrap_or(0.0);
+            let observed = total_log_data.errors as f64;
+            println!("\nObserved errors: {observed}");
+            match fitted.get_expected_total() {
+                Some(total) => println!(
+                    "Expected total errors: {:.3}, remaining: {:.3}",
+                    total,
+                    total - observed
+                ),
+                None => println!("The {name} model expects errors to keep coming without end"),
+            }
+            println!(
+                "Current failure intensity: {:.3} errors per {unit}",
+                fitted.get_intensity(end)
+            );
+            println!(
+                "Chance of no errors in the next {mission} {}: {:.2}%",
+                plural(mission, unit),
+                fitted.get_reliability(end, mission) * 100.0
+            );
+            if let Some(target) = target_intensity {
+                match fitted.get_time_to_target(end, target) {
+                    Ok(Some(0.0)) => println!(
+                        "The failure intensity is already at most {target} errors per {unit}"
+                    ),
+                    Ok(Some(ahead)) => println!(
+                        "The failure intensity falls to {target} errors per {unit} in {ahead:.3} {}",
+                        plural(ahead, unit)
+                    ),
+                    Ok(None) => println!(
+                        "The failure intensity doesn't fall to {target} errors per {unit} within a billion {unit}s"
+                    ),
+                    Err(error) => eprintln!(
+                        "Unable to find when the failure intensity falls to {target}: {error}"
+                    ),
+                }
+            }
+            // Each period is as long as a bucket, adding as much usage as the average bucket
+            let period_exposure = match exposure {
+                Exposure::Time => log_analyzer.time_multi as f64 / unit_seconds,
+                _ => end / log_data_by_time.len() as f64,
+            };
+            let end_time = log_data_by_time.last().unwrap().end_time;
+            let periods: Vec<(DateTime<Utc>, f64)> = (1..=periods as i64)
+                .map(|period| {
+                    (
+                        end_time + Duration::seconds(log_analyzer.time_multi * period),
+                        end + period_exposure * period as f64,
+                    )
+                })
+                .collect();
+            println!(
+                "\n{}",
+                table::get_forecast_table(format, &fitted, observed, end, &periods)
             );
         }
+        Commands::Backtest {
+            train,
+            rolling,
+            horizon,
+            step,
+            model,
+            fit,
+            unit,
+            exposure,
+            format,
+        } => {
+            let unit_seconds = get_time_multiplier(unit.as_deref().unwrap_or(time)).unwrap() as f64;
+            let length = log_data_by_time.len();
+            let first = (length as f64 * train / 100.0).round() as usize;
+            if first == 0 || first >= length {
+                eprintln!("Training on {train}% of {length} buckets leaves none to fit or predict");
+                return;
+            }
+            if horizon == 0 || step == 0 {
+                eprintln!("The horizon and step have to be at least one bucket");
+                return;
+            }
+            let (origins, horizon): (Vec<usize>, usize) = match rolling {
+                true => ((first..length).step_by(step).collect(), horizon),
+                false => (vec![first], length - first),
+            };
+            match rolling {
+                true => println!(
+                    "Refit at {} origins from {time} {first} of {length}, predicting {horizon} {} ahead each time\n",
+                    origins.len(),
+                    plural(horizon as f64, time)
+                ),
+                false => println!(
+                    "Fit to the first {first} of {length} {time}s and predicted the remaining {horizon}\n"
+                ),
+            }
+            let mut ranked = Vec::new();
+            for model in model.get_models() {
+                match backtest::backtest(
+                    model,
+                    fit,
+                    &log_data_by_time,
+                    exposure,
+                    unit_seconds,
+                    &origins,
+                    horizon,
+                ) {
+                    Ok(result) => ranked.push((model, result)),
+                    Err(error) => eprintln!("Unable to backtest the {model} model: {error}"),
+                }
+            }
+            // Models that couldn't be fit at every origin go after the ones that could
+            ranked.sort_by(|a, b| {
+                a.1.failed
+                    .cmp(&b.1.failed)
+                    .then(b.1.mean_log_likelihood.total_cmp(&a.1.mean_log_likelihood))
+            });
+            println!("{}", table::get_backtest_table(format, &ranked));
+        }
     }
 }
@@ -0,0 +1,102 @@
+use std::{
+    fs::{create_dir_all, write},
+    path::Path,
+};
+
+use chrono::SecondsFormat;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::LogData;
+
+/// Machine readable formats the log data can be written in
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum OutputFormat {
+    Json,
+    Csv,
+    Ndjson,
+    /// Self contained page with tables and interactive charts, written to the report path
+    Html,
+}
+
+/// Single `LogData` labeled with the bucket it belongs to
+#[derive(Serialize)]
+struct Row<'a> {
+    bucket: String,
+    #[serde(flatten)]
+    data: &'a LogData,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    total: &'a LogData,
+    buckets: &'a [LogData],
+}
+
+fn get_rows<'a>(total: &'a LogData, buckets: &'a [LogData]) -> Vec<Row<'a>> {
+    let mut rows = vec![Row {
+        bucket: "total".to_string(),
+        data: total,
+    }];
+    for (index, data) in buckets.iter().enumerate() {
+        rows.push(Row {
+            bucket: index.to_string(),
+            data,
+        });
+    }
+    rows
+}
+
+fn get_csv(rows: &[Row]) -> String {
+    let mut csv = String::from(
+        "bucket,start_time,end_time,time,users,sessions,total_bytes,avg_bytes,log_count,errors,atbl,atbe,2xx,3xx,4xx,5xx\n",
+    );
+    for row in rows {
+        let data = row.data;
+        csv += &format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            row.bucket,
+            data.start_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            data.end_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            data.time,
+            data.users,
+            data.sessions,
+            data.total_bytes,
+            data.avg_bytes,
+            data.log_count,
+            data.errors,
+            data.atbl,
+            data.atbe,
+            data.status_classes[0],
+            data.status_classes[1],
+            data.status_classes[2],
+            data.status_classes[3]
+        );
+    }
+    csv
+}
+
+/// Formats the total and per bucket log data in the given format
+pub(crate) fn format_data(format: OutputFormat, total: &LogData, buckets: &[LogData]) -> String {
+    match format {
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(&Report { total, buckets }).expect("unable to write json")
+        }
+        OutputFormat::Csv => get_csv(&get_rows(total, buckets)),
+        OutputFormat::Ndjson => get_rows(total, buckets)
+            .iter()
+            .map(|row| serde_json::to_string(row).expect("unable to write json"))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        OutputFormat::Html => unreachable!("html reports are built by report::get_report"),
+    }
+}
+
+/// Writes the formatted data to its own file, creating the parent directories when needed
+pub(crate) fn save_data(path: &Path, data: &str) {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).expect("unable to create the output directory");
+    }
+    write(path, format!("{}\n", data)).expect("unable to write the output");
+    println!("Data saved to {}", path.display());
+}
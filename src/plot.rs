@@ -1,116 +1,593 @@
-use plotters::prelude::*;
+use std::{
+    fs::create_dir_all,
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
-fn get_y_axis_range(data: &Vec<(f64, f64)>) -> f64 {
-    let mut max = 0.0;
-    for value in data {
-        if value.1 > max {
-            max = value.1
+use chrono::{DateTime, TimeZone, Utc};
+use plotters::{
+    coord::{
+        ranged1d::{AsRangedCoord, ValueFormatter},
+        Shift,
+    },
+    prelude::*,
+    style::text_anchor::{HPos, Pos, VPos},
+};
+use serde::Serialize;
+
+/// Image formats graphs can be saved as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ImageFormat {
+    Png,
+    Svg,
+}
+
+/// Where and how large a graph is drawn
+#[derive(Clone, Debug)]
+pub(crate) struct PlotConfig {
+    pub(crate) path: PathBuf,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl PlotConfig {
+    pub(crate) fn get_format(&self) -> Option<ImageFormat> {
+        get_image_format(&self.path)
+    }
+}
+
+/// Infers the image format from the extension of a path
+fn get_image_format(path: &Path) -> Option<ImageFormat> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "png" => Some(ImageFormat::Png),
+        "svg" => Some(ImageFormat::Svg),
+        _ => None,
+    }
+}
+
+/// Checks that a graph output path has an extension a graph can be saved as
+pub(crate) fn parse_plot_path(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    match get_image_format(&path) {
+        Some(_) => Ok(path),
+        None => Err("graphs can only be saved as .png or .svg".to_string()),
+    }
+}
+
+/// Runs the drawing code on a drawing area backed by the configured file
+macro_rules! draw_to_file {
+    ($config:expr, |$root_area:ident| $draw:expr) => {{
+        let config: &PlotConfig = $config;
+        if let Some(parent) = config.path.parent() {
+            create_dir_all(parent).expect("unable to create graph directory");
+        }
+        let size = (config.width, config.height);
+        match config.get_format() {
+            Some(ImageFormat::Svg) => {
+                let $root_area = SVGBackend::new(&config.path, size).into_drawing_area();
+                $draw;
+                $root_area.present().unwrap();
+            }
+            _ => {
+                let $root_area = BitMapBackend::new(&config.path, size).into_drawing_area();
+                $draw;
+                $root_area.present().unwrap();
+            }
+        }
+        println!("Graph saved to {}", config.path.display());
+    }};
+}
+
+/// Named set of points drawn as one line on a graph
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Series {
+    pub(crate) name: String,
+    pub(crate) points: Vec<(f64, f64)>,
+    /// Draws the series against the y-axis on the right side of the graph
+    pub(crate) secondary: bool,
+    /// Lower and upper bound around the line at each x as `(x, low, high)`, drawn shaded
+    pub(crate) band: Vec<(f64, f64, f64)>,
+}
+
+impl Series {
+    pub(crate) fn new(name: &str, points: Vec<(f64, f64)>) -> Series {
+        Series {
+            name: name.to_string(),
+            points,
+            secondary: false,
+            band: Vec::new(),
+        }
+    }
+
+    pub(crate) fn secondary(name: &str, points: Vec<(f64, f64)>) -> Series {
+        Series {
+            secondary: true,
+            ..Series::new(name, points)
+        }
+    }
+
+    pub(crate) fn with_band(self, band: Vec<(f64, f64, f64)>) -> Series {
+        Series { band, ..self }
+    }
+}
+
+/// Whether a value can be drawn on a linear or logarithmic axis
+fn is_drawable(value: f64, log_scale: bool) -> bool {
+    value.is_finite() && (!log_scale || value > 0.0)
+}
+
+/// Lowest and highest values that can be drawn, or `None` when there are none
+fn get_bounds(values: impl Iterator<Item = f64>, log_scale: bool) -> Option<(f64, f64)> {
+    values
+        .filter(|x| is_drawable(*x, log_scale))
+        .fold(None, |bounds, x| match bounds {
+            None => Some((x, x)),
+            Some((low, high)) => Some((low.min(x), high.max(x))),
+        })
+}
+
+/// Widens a range that holds a single value so it can still be drawn
+fn widen_range(low: f64, high: f64, log_scale: bool) -> Range<f64> {
+    match (low < high, log_scale) {
+        (true, _) => low..high,
+        (false, true) => low / 2.0..high * 2.0,
+        (false, false) => low - 1.0..high + 1.0,
+    }
+}
+
+fn get_x_axis_range(series: &[Series], log_scale: bool) -> Option<Range<f64>> {
+    let points = series.iter().flat_map(|x| &x.points);
+    let (low, high) = get_bounds(points.map(|x| x.0), log_scale)?;
+    Some(widen_range(low, high, log_scale))
+}
+
+/// Range of a y-axis holding every value with room left above them for the legend
+fn get_y_axis_range(series: &[&Series], log_scale: bool) -> Range<f64> {
+    let points = series.iter().flat_map(|x| &x.points).map(|x| x.1);
+    let band = series.iter().flat_map(|x| &x.band).flat_map(|x| [x.1, x.2]);
+    match (get_bounds(points.chain(band), log_scale), log_scale) {
+        (None, true) => 1.0..10.0,
+        (None, false) => 0.0..1.0,
+        (Some((low, high)), true) => widen_range(low / 2.0, high * 4.0, true),
+        (Some((low, high)), false) => {
+            // Keeps zero on the axis so values aren't exaggerated
+            let (low, high) = (low.min(0.0), high.max(0.0));
+            let span = match high > low {
+                true => high - low,
+                false => 1.0,
+            };
+            let padding = match low < 0.0 {
+                true => span * 0.1,
+                false => 0.0,
+            };
+            low - padding..high + span * 0.25
         }
     }
-    return max;
 }
 
-pub(crate) fn plot_graph(
-    low_bound: f64,
-    x_range: f64,
-    y_axis: String,
-    x_axis: String,
-    data_point: Vec<(f64, f64)>,
+/// Graph made of one or more lines
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct LineGraph {
+    pub(crate) title: String,
+    pub(crate) x_axis: String,
+    pub(crate) y_axis: String,
+    /// Reads the x values as unix timestamps and labels them as dates
+    pub(crate) time_axis: bool,
+    /// Draws the x-axis on a logarithmic scale, ignored for time axes
+    pub(crate) log_x: bool,
+    /// Draws the primary y-axis on a logarithmic scale
+    pub(crate) log_y: bool,
+    pub(crate) series: Vec<Series>,
+}
+
+impl LineGraph {
+    fn has_log_x(&self) -> bool {
+        self.log_x && !self.time_axis
+    }
+}
+
+/// Single graph drawn in one cell of a dashboard
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Panel {
+    Lines(LineGraph),
+    /// Horizontal bars with one labeled bar per value
+    Bars {
+        title: String,
+        x_axis: String,
+        bars: Vec<(String, f64)>,
+    },
+}
+
+/// Draws any number of series on one graph, each with its own color and legend entry
+pub(crate) fn plot_series(config: &PlotConfig, graph: LineGraph) {
+    if get_x_axis_range(&graph.series, graph.has_log_x()).is_none() {
+        eprintln!(
+            "Nothing to plot: {} has no values that can be drawn",
+            graph.y_axis
+        );
+        return;
+    }
+    draw_to_file!(config, |root_area| draw_line_graph(&root_area, &graph));
+}
+
+/// Draws every panel in a grid on a single image
+pub(crate) fn plot_dashboard(config: &PlotConfig, columns: usize, panels: Vec<Panel>) {
+    draw_to_file!(config, |root_area| {
+        root_area.fill(&WHITE).unwrap();
+        let rows = panels.len().div_ceil(columns);
+        let areas = root_area.split_evenly((rows, columns));
+        for (area, panel) in areas.iter().zip(panels) {
+            match panel {
+                Panel::Lines(graph) => draw_line_graph(area, &graph),
+                Panel::Bars {
+                    title,
+                    x_axis,
+                    bars,
+                } => draw_bars(area, &title, &x_axis, bars),
+            }
+        }
+    });
+}
+
+/// Draws one labeled horizontal bar per value with the first value at the top
+pub(crate) fn plot_bars(config: &PlotConfig, title: &str, x_axis: &str, bars: Vec<(String, f64)>) {
+    if bars.is_empty() {
+        eprintln!("Nothing to plot: {} has no values", title);
+        return;
+    }
+    draw_to_file!(config, |root_area| draw_bars(
+        &root_area, title, x_axis, bars
+    ));
+}
+
+/// Draws how many values fall into each of `bins` equally sized ranges
+pub(crate) fn plot_histogram(
+    config: &PlotConfig,
+    title: &str,
+    x_axis: &str,
+    values: Vec<f64>,
+    bins: usize,
 ) {
-    let y_range = get_y_axis_range(&data_point);
-    let root_area = BitMapBackend::new("images/2.8.png", (1400, 1000)).into_drawing_area();
-    root_area.fill(&WHITE).unwrap();
+    if get_bounds(values.iter().copied(), false).is_none() {
+        eprintln!("Nothing to plot: {} has no values that can be drawn", title);
+        return;
+    }
+    draw_to_file!(config, |root_area| draw_histogram(
+        &root_area, title, x_axis, values, bins
+    ));
+}
 
-    let mut ctx = ChartBuilder::on(&root_area)
+/// Draws the traffic of every hour of every day of the week, with Monday as the first day
+pub(crate) fn plot_heatmap(config: &PlotConfig, title: &str, traffic: [[f64; 24]; 7]) {
+    draw_to_file!(config, |root_area| draw_heatmap(&root_area, title, traffic));
+}
+
+fn draw_histogram<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_axis: &str,
+    values: Vec<f64>,
+    bins: usize,
+) {
+    let (min, max) = get_bounds(values.iter().copied(), false).unwrap();
+    // Every value lands in the same bin when they are all equal
+    let width = match max > min {
+        true => (max - min) / bins as f64,
+        false => 1.0,
+    };
+    let mut counts = vec![0.0; bins];
+    for value in values.iter().filter(|x| x.is_finite()) {
+        let bin = ((value - min) / width) as usize;
+        counts[bin.min(bins - 1)] += 1.0;
+    }
+    let highest = counts.iter().copied().fold(0.0, f64::max);
+    area.fill(&WHITE).unwrap();
+
+    let mut ctx = ChartBuilder::on(area)
         .set_label_area_size(LabelAreaPosition::Left, 100)
         .set_label_area_size(LabelAreaPosition::Bottom, 100)
-        .caption("Log Data", ("sans-serif", 40))
-        .build_cartesian_2d(low_bound..x_range, 0.0..y_range * 1.25)
+        .caption(title, ("sans-serif", 40))
+        .build_cartesian_2d(min..min + width * bins as f64, 0.0..highest * 1.1)
         .unwrap();
 
     ctx.configure_mesh()
         .disable_x_mesh()
         .bold_line_style(WHITE.mix(0.3))
-        .y_desc(&y_axis)
-        .x_desc(&x_axis)
+        .y_desc("requests")
+        .x_desc(x_axis)
         .axis_desc_style(("sans-serif", 30))
         .draw()
         .unwrap();
 
-    ctx.configure_mesh().draw().unwrap();
-
-    ctx.draw_series(data_point.iter().map(|point| Circle::new(*point, 2, &BLUE)))
-        .unwrap();
-    ctx.draw_series(LineSeries::new(
-        data_point.into_iter().map(|point| (point.0, point.1)),
-        &BLUE,
-    ))
+    ctx.draw_series(counts.iter().enumerate().map(|(i, count)| {
+        let start = min + width * i as f64;
+        let mut bar = Rectangle::new(
+            [(start, 0.0), (start + width, *count)],
+            Palette99::pick(0).filled(),
+        );
+        bar.set_margin(0, 0, 1, 1);
+        bar
+    }))
     .unwrap();
 }
 
-pub(crate) fn plot_double_graph(
-    low_bound: f64,
-    x_range: f64,
-    y_axis: String,
-    x_axis: String,
-    data_point: Vec<(f64, f64)>,
-    second_point: Vec<(f64, f64)>,
+fn draw_heatmap<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    title: &str,
+    traffic: [[f64; 24]; 7],
 ) {
-    let mut y_range = get_y_axis_range(&second_point);
-    if get_y_axis_range(&data_point) > y_range {
-        y_range = get_y_axis_range(&data_point);
-    }
-    let root_area = BitMapBackend::new("images/2.8.png", (1400, 1000)).into_drawing_area();
-    root_area.fill(&WHITE).unwrap();
+    let days = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let highest = traffic.iter().flatten().copied().fold(0.0, f64::max);
+    area.fill(&WHITE).unwrap();
 
-    let mut ctx = ChartBuilder::on(&root_area)
+    let mut ctx = ChartBuilder::on(area)
         .set_label_area_size(LabelAreaPosition::Left, 100)
         .set_label_area_size(LabelAreaPosition::Bottom, 100)
-        .caption("Log Data", ("sans-serif", 40))
-        .build_cartesian_2d(low_bound..x_range, 0.0..y_range * 1.25)
+        .caption(title, ("sans-serif", 40))
+        .build_cartesian_2d((0..23_u32).into_segmented(), (0..6_usize).into_segmented())
         .unwrap();
 
     ctx.configure_mesh()
-        .disable_x_mesh()
-        .bold_line_style(WHITE.mix(0.3))
-        .y_desc(&y_axis)
-        .x_desc(&x_axis)
+        .disable_mesh()
+        .x_labels(24)
+        .y_labels(7)
+        .x_desc("hour of day (UTC)")
+        .y_desc("day of week")
+        .x_label_formatter(&|value| match value {
+            SegmentValue::CenterOf(hour) => format!("{:02}", hour),
+            _ => String::new(),
+        })
+        // Days are drawn bottom up so Monday ends up at the top
+        .y_label_formatter(&|value| match value {
+            SegmentValue::CenterOf(day) => days[6 - day].to_string(),
+            _ => String::new(),
+        })
         .axis_desc_style(("sans-serif", 30))
         .draw()
         .unwrap();
 
-    ctx.configure_mesh().draw().unwrap();
+    ctx.draw_series(traffic.iter().enumerate().flat_map(|(day, hours)| {
+        hours.iter().enumerate().map(move |(hour, count)| {
+            let shade = match highest > 0.0 {
+                true => count / highest,
+                false => 0.0,
+            };
+            let (hour, row) = (hour as u32, 6 - day);
+            Rectangle::new(
+                [
+                    (SegmentValue::Exact(hour), SegmentValue::Exact(row)),
+                    (SegmentValue::Exact(hour + 1), SegmentValue::Exact(row + 1)),
+                ],
+                ViridisRGB::get_color(shade).filled(),
+            )
+        })
+    }))
+    .unwrap();
+}
+
+fn draw_bars<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_axis: &str,
+    bars: Vec<(String, f64)>,
+) {
+    let max = bars.iter().map(|x| x.1).fold(0.0, f64::max);
+    // Bars are drawn bottom up so the first bar ends up at the top
+    let labels: Vec<String> = bars.iter().rev().map(|x| x.0.clone()).collect();
+    area.fill(&WHITE).unwrap();
 
-    ctx.draw_series(data_point.iter().map(|point| Circle::new(*point, 2, &RED)))
+    let mut ctx = ChartBuilder::on(area)
+        .set_label_area_size(LabelAreaPosition::Left, 160)
+        .set_label_area_size(LabelAreaPosition::Bottom, 60)
+        .caption(title, ("sans-serif", 30))
+        .build_cartesian_2d(
+            0.0..max * 1.1,
+            (0..bars.len().saturating_sub(1)).into_segmented(),
+        )
         .unwrap();
-    ctx.draw_series(LineSeries::new(
-        data_point.into_iter().map(|point| (point.0, point.1)),
-        &RED,
-    ))
-    .unwrap()
-    .label("Errors Overtime")
-    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &RED));
 
-    ctx.draw_series(
-        second_point
-            .iter()
-            .map(|point| Circle::new(*point, 2, &BLUE)),
-    )
+    ctx.configure_mesh()
+        .disable_y_mesh()
+        .bold_line_style(WHITE.mix(0.3))
+        .x_desc(x_axis)
+        .y_label_formatter(&|value| match value {
+            SegmentValue::CenterOf(i) => labels.get(*i).cloned().unwrap_or_default(),
+            _ => String::new(),
+        })
+        .axis_desc_style(("sans-serif", 20))
+        .draw()
+        .unwrap();
+
+    ctx.draw_series(bars.iter().rev().enumerate().map(|(i, bar)| {
+        let color = Palette99::pick(labels.len() - 1 - i).to_rgba();
+        Rectangle::new(
+            [
+                (0.0, SegmentValue::Exact(i)),
+                (bar.1, SegmentValue::Exact(i + 1)),
+            ],
+            color.filled(),
+        )
+    }))
     .unwrap();
-    ctx.draw_series(LineSeries::new(
-        second_point.into_iter().map(|point| (point.0, point.1)),
-        &BLUE,
+}
+
+/// Formats an axis value into the label shown at its tick
+type LabelFormatter<'a, T> = &'a dyn Fn(&T) -> String;
+
+fn to_date_time(timestamp: f64) -> DateTime<Utc> {
+    Utc.timestamp_opt(timestamp as i64, 0).unwrap()
+}
+
+/// Picks a date format that keeps the tick labels readable for the time span
+fn get_date_format(seconds: f64) -> &'static str {
+    match seconds {
+        x if x <= 3600.0 => "%H:%M:%S",
+        x if x > 31556952.0 => "%Y-%m-%d",
+        _ => "%m-%d %H:%M",
+    }
+}
+
+fn draw_line_graph<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, graph: &LineGraph) {
+    let Some(x_range) = get_x_axis_range(&graph.series, graph.has_log_x()) else {
+        return draw_empty(area, &graph.title);
+    };
+    match (graph.time_axis, graph.log_x) {
+        (true, _) => {
+            let format = get_date_format(x_range.end - x_range.start);
+            let x_format = |x: &DateTime<Utc>| x.format(format).to_string();
+            let x_range = to_date_time(x_range.start)..to_date_time(x_range.end);
+            draw_with_y_scale(
+                area,
+                graph,
+                RangedDateTime::from(x_range),
+                to_date_time,
+                Some(&x_format),
+            )
+        }
+        (false, true) => draw_with_y_scale(area, graph, x_range.log_scale(), |x| x, None),
+        (false, false) => draw_with_y_scale(area, graph, x_range, |x| x, None),
+    }
+}
+
+/// Marks a panel that has nothing to draw
+fn draw_empty<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, title: &str) {
+    area.fill(&WHITE).unwrap();
+    let (width, height) = area.dim_in_pixel();
+    let style = ("sans-serif", 30)
+        .into_font()
+        .color(&BLACK)
+        .pos(Pos::new(HPos::Center, VPos::Center));
+    let text = format!("{}: no data to plot", title);
+    area.draw(&Text::new(
+        text,
+        (width as i32 / 2, height as i32 / 2),
+        style,
     ))
-    .unwrap()
-    .label("Model")
-    .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLUE));
+    .unwrap();
+}
+
+fn draw_with_y_scale<DB, X>(
+    area: &DrawingArea<DB, Shift>,
+    graph: &LineGraph,
+    x_range: X,
+    to_x: impl Fn(f64) -> X::Value,
+    x_format: Option<LabelFormatter<X::Value>>,
+) where
+    DB: DrawingBackend,
+    X: AsRangedCoord + Clone,
+    X::CoordDescType: ValueFormatter<X::Value>,
+    X::Value: Clone + 'static,
+{
+    let primary: Vec<&Series> = graph.series.iter().filter(|x| !x.secondary).collect();
+    let y_range = get_y_axis_range(&primary, graph.log_y);
+    match graph.log_y {
+        true => draw_lines(area, graph, x_range, y_range.log_scale(), to_x, x_format),
+        false => draw_lines(area, graph, x_range, y_range, to_x, x_format),
+    }
+}
+
+fn draw_lines<DB, X, Y>(
+    root_area: &DrawingArea<DB, Shift>,
+    graph: &LineGraph,
+    x_range: X,
+    y_range: Y,
+    to_x: impl Fn(f64) -> X::Value,
+    x_format: Option<LabelFormatter<X::Value>>,
+) where
+    DB: DrawingBackend,
+    X: AsRangedCoord + Clone,
+    X::CoordDescType: ValueFormatter<X::Value>,
+    X::Value: Clone + 'static,
+    Y: AsRangedCoord<Value = f64>,
+    Y::CoordDescType: ValueFormatter<f64>,
+{
+    let series = &graph.series;
+    let secondary: Vec<&Series> = series.iter().filter(|x| x.secondary).collect();
+    root_area.fill(&WHITE).unwrap();
+
+    let mut builder = ChartBuilder::on(root_area);
+    builder
+        .set_label_area_size(LabelAreaPosition::Left, 100)
+        .set_label_area_size(LabelAreaPosition::Bottom, 100)
+        .caption(&graph.title, ("sans-serif", 40));
+    if !secondary.is_empty() {
+        builder.set_label_area_size(LabelAreaPosition::Right, 100);
+    }
+    let mut ctx = builder
+        .build_cartesian_2d(x_range.clone(), y_range)
+        .unwrap()
+        .set_secondary_coord(x_range, get_y_axis_range(&secondary, false));
+
+    let mut mesh = ctx.configure_mesh();
+    mesh.disable_x_mesh()
+        .bold_line_style(WHITE.mix(0.3))
+        .y_desc(&graph.y_axis)
+        .x_desc(&graph.x_axis)
+        .axis_desc_style(("sans-serif", 30));
+    if let Some(x_format) = x_format {
+        mesh.x_label_formatter(x_format);
+    }
+    mesh.draw().unwrap();
+
+    if !secondary.is_empty() {
+        let names: Vec<&str> = secondary.iter().map(|x| x.name.as_str()).collect();
+        ctx.configure_secondary_axes()
+            .y_desc(names.join(", "))
+            .axis_desc_style(("sans-serif", 30))
+            .draw()
+            .unwrap();
+    }
+
+    for (index, line) in series.iter().enumerate() {
+        let color = Palette99::pick(index).to_rgba();
+        // Points that can't be placed on the axes, like NaN or zero on a log scale, are skipped
+        let log_y = graph.log_y && !line.secondary;
+        let points: Vec<(X::Value, f64)> = line
+            .points
+            .iter()
+            .filter(|x| is_drawable(x.0, graph.has_log_x()) && is_drawable(x.1, log_y))
+            .map(|x| (to_x(x.0), x.1))
+            .collect();
+        let band: Vec<(X::Value, f64)> = line
+            .band
+            .iter()
+            .map(|x| (x.0, x.2))
+            .chain(line.band.iter().rev().map(|x| (x.0, x.1)))
+            .filter(|x| is_drawable(x.0, graph.has_log_x()) && is_drawable(x.1, log_y))
+            .map(|x| (to_x(x.0), x.1))
+            .collect();
+        if !band.is_empty() {
+            let area = Polygon::new(band, color.mix(0.2).filled());
+            match line.secondary {
+                true => ctx.draw_secondary_series([area]).unwrap(),
+                false => ctx.draw_series([area]).unwrap(),
+            };
+        }
+        let circles = points
+            .iter()
+            .map(|point| Circle::new(point.clone(), 2, color));
+        let drawn = match line.secondary {
+            true => {
+                ctx.draw_secondary_series(circles).unwrap();
+                ctx.draw_secondary_series(LineSeries::new(points.clone(), color))
+                    .unwrap()
+            }
+            false => {
+                ctx.draw_series(circles).unwrap();
+                ctx.draw_series(LineSeries::new(points.clone(), color))
+                    .unwrap()
+            }
+        };
+        drawn
+            .label(&line.name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
 
     ctx.configure_series_labels()
-        .border_style(&BLACK)
-        .background_style(&WHITE.mix(0.8))
+        .border_style(BLACK)
+        .background_style(WHITE.mix(0.8))
         .draw()
         .unwrap();
 }
-
-//let (stats, log_count) = count_status_code(log_data);
-//println!("{:?}\n{}", stats, log_count)
@@ -0,0 +1,270 @@
+// Draws the panels embedded in the report as zoomable charts with hover tooltips
+const NS = 'http://www.w3.org/2000/svg';
+const COLORS = ['#e6194b', '#3cb44b', '#4363d8', '#f58231', '#911eb4', '#42d4f4', '#f032e6', '#9a6324'];
+const W = 640, H = 360;
+const M = { left: 70, right: 70, top: 20, bottom: 40 };
+let clipCount = 0;
+
+function el(tag, attrs, parent) {
+  const node = document.createElementNS(NS, tag);
+  for (const key in attrs) node.setAttribute(key, attrs[key]);
+  if (parent) parent.appendChild(node);
+  return node;
+}
+
+function text(parent, attrs, value) {
+  el('text', attrs, parent).textContent = value;
+}
+
+function escape(value) {
+  return String(value).replace(/[&<>"]/g, c => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;' })[c]);
+}
+
+function fmtNum(value) {
+  const size = Math.abs(value);
+  if (size !== 0 && (size >= 1e6 || size < 1e-3)) return value.toExponential(3);
+  return String(+value.toFixed(3));
+}
+
+function fmtDate(seconds, span) {
+  const date = new Date(seconds * 1000).toISOString();
+  if (span === undefined) return date.replace('T', ' ').slice(0, 19);
+  if (span > 3 * 86400) return date.slice(0, 10);
+  if (span > 3600) return date.slice(5, 16).replace('T', ' ');
+  return date.slice(11, 19);
+}
+
+// Evenly spaced round values between `low` and `high`
+function ticks(low, high, count) {
+  const rough = (high - low) / count;
+  const magnitude = Math.pow(10, Math.floor(Math.log10(rough)));
+  const step = [1, 2, 5, 10].map(x => x * magnitude).find(x => x >= rough);
+  const values = [];
+  for (let value = Math.ceil(low / step) * step; value <= high + step * 1e-9; value += step) {
+    values.push(value);
+  }
+  return values;
+}
+
+function usable(value, log) {
+  return value !== null && isFinite(value) && (!log || value > 0);
+}
+
+// Range of the y values inside the x range, keeping zero on linear axes
+function yRange(series, log, scaleX, view) {
+  const inView = x => scaleX(x) >= view[0] && scaleX(x) <= view[1];
+  const values = series.flatMap(s => s.points.filter(p => inView(p[0])).map(p => p[1])
+    .concat(s.band.filter(b => inView(b[0])).flatMap(b => [b[1], b[2]])))
+    .filter(value => usable(value, log))
+    .map(value => log ? Math.log10(value) : value);
+  if (!values.length) return [0, 1];
+  let low = Math.min(...values), high = Math.max(...values);
+  if (!log) {
+    low = Math.min(low, 0);
+    high = Math.max(high, 0);
+  }
+  if (low === high) {
+    low -= 1;
+    high += 1;
+  }
+  const padding = (high - low) * 0.05;
+  return [low === 0 ? 0 : low - padding, high + padding];
+}
+
+function drawLines(box, graph) {
+  const logX = graph.log_x && !graph.time_axis;
+  const scaleX = value => logX ? Math.log10(value) : value;
+  const series = graph.series.map((s, i) => ({
+    name: s.name,
+    secondary: s.secondary,
+    color: COLORS[i % COLORS.length],
+    points: s.points.filter(p => usable(p[0], logX) && usable(p[1], graph.log_y && !s.secondary)),
+    band: s.band.filter(b => usable(b[0], logX) && [b[1], b[2]].every(y => usable(y, graph.log_y && !s.secondary))),
+  }));
+  const xs = series.flatMap(s => s.points.map(p => scaleX(p[0])));
+  if (!xs.length) {
+    box.append('no data to plot');
+    return;
+  }
+  const full = [Math.min(...xs), Math.max(...xs)];
+  if (full[0] === full[1]) {
+    full[0] -= 1;
+    full[1] += 1;
+  }
+  let view = full.slice();
+  const svg = el('svg', { viewBox: `0 0 ${W} ${H}`, class: 'chart' }, box);
+  const legend = document.createElement('div');
+  legend.className = 'legend';
+  for (const s of series) {
+    const entry = document.createElement('span');
+    entry.innerHTML = `<i style="background:${s.color}"></i>`;
+    entry.append(s.name + (s.secondary ? ' (right)' : ''));
+    legend.appendChild(entry);
+  }
+  box.appendChild(legend);
+  const tip = document.createElement('div');
+  tip.className = 'tip';
+  box.appendChild(tip);
+  const clip = `clip${clipCount++}`;
+  let toX, fromX, axes;
+
+  function render() {
+    svg.textContent = '';
+    const plotW = W - M.left - M.right, plotH = H - M.top - M.bottom;
+    toX = value => M.left + (scaleX(value) - view[0]) / (view[1] - view[0]) * plotW;
+    fromX = px => view[0] + (px - M.left) / plotW * (view[1] - view[0]);
+    axes = [false, true].map(secondary => {
+      const log = graph.log_y && !secondary;
+      const list = series.filter(s => s.secondary === secondary);
+      const range = yRange(list, log, scaleX, view);
+      const toY = value => H - M.bottom - ((log ? Math.log10(value) : value) - range[0]) / (range[1] - range[0]) * plotH;
+      return { list, log, range, toY };
+    });
+    el('rect', { x: M.left, y: M.top, width: plotW, height: plotH }, el('clipPath', { id: clip }, svg));
+
+    const span = logX ? undefined : view[1] - view[0];
+    for (const tick of ticks(view[0], view[1], 6)) {
+      const x = M.left + (tick - view[0]) / (view[1] - view[0]) * plotW;
+      el('line', { x1: x, x2: x, y1: M.top, y2: H - M.bottom, class: 'grid' }, svg);
+      const label = graph.time_axis ? fmtDate(tick, span) : fmtNum(logX ? Math.pow(10, tick) : tick);
+      text(svg, { x, y: H - M.bottom + 16, 'text-anchor': 'middle' }, label);
+    }
+    text(svg, { x: M.left + plotW / 2, y: H - 4, 'text-anchor': 'middle', class: 'label' }, graph.x_axis);
+    axes.forEach((axis, i) => {
+      if (!axis.list.length) return;
+      const x = i === 0 ? M.left : W - M.right;
+      for (const tick of ticks(axis.range[0], axis.range[1], 5)) {
+        const y = H - M.bottom - (tick - axis.range[0]) / (axis.range[1] - axis.range[0]) * plotH;
+        if (i === 0) el('line', { x1: M.left, x2: W - M.right, y1: y, y2: y, class: 'grid' }, svg);
+        const label = fmtNum(axis.log ? Math.pow(10, tick) : tick);
+        text(svg, { x: i === 0 ? x - 6 : x + 6, y: y + 4, 'text-anchor': i === 0 ? 'end' : 'start' }, label);
+      }
+      el('line', { x1: x, x2: x, y1: M.top, y2: H - M.bottom, class: 'axis' }, svg);
+    });
+    el('line', { x1: M.left, x2: W - M.right, y1: H - M.bottom, y2: H - M.bottom, class: 'axis' }, svg);
+    if (graph.y_axis) {
+      text(svg, { x: 14, y: M.top + plotH / 2, transform: `rotate(-90 14 ${M.top + plotH / 2})`, 'text-anchor': 'middle', class: 'label' }, graph.y_axis);
+    }
+
+    const lines = el('g', { 'clip-path': `url(#${clip})` }, svg);
+    for (const s of series) {
+      const toY = axes[s.secondary ? 1 : 0].toY;
+      if (s.band.length) {
+        const edge = s.band.map(b => [b[0], b[2]]).concat(s.band.map(b => [b[0], b[1]]).reverse());
+        const area = edge.map((p, i) => `${i ? 'L' : 'M'}${toX(p[0]).toFixed(1)},${toY(p[1]).toFixed(1)}`).join('') + 'Z';
+        el('path', { d: area, fill: s.color, 'fill-opacity': 0.2, stroke: 'none' }, lines);
+      }
+      const path = s.points.map((p, i) => `${i ? 'L' : 'M'}${toX(p[0]).toFixed(1)},${toY(p[1]).toFixed(1)}`).join('');
+      el('path', { d: path, stroke: s.color, fill: 'none', 'stroke-width': 1.5 }, lines);
+      if (s.points.length <= 100) {
+        for (const p of s.points) el('circle', { cx: toX(p[0]), cy: toY(p[1]), r: 2.5, fill: s.color }, lines);
+      }
+    }
+    el('line', { class: 'guide', y1: M.top, y2: H - M.bottom, visibility: 'hidden' }, svg);
+    el('rect', { class: 'selection', y: M.top, height: plotH, width: 0, visibility: 'hidden' }, svg);
+  }
+
+  function toSvgX(event) {
+    const point = svg.createSVGPoint();
+    point.x = event.clientX;
+    point.y = event.clientY;
+    return point.matrixTransform(svg.getScreenCTM().inverse()).x;
+  }
+
+  let dragStart = null;
+  svg.addEventListener('mousedown', event => {
+    dragStart = toSvgX(event);
+    event.preventDefault();
+  });
+  svg.addEventListener('mousemove', event => {
+    const x = toSvgX(event);
+    const selection = svg.querySelector('.selection');
+    if (dragStart !== null) {
+      selection.setAttribute('x', Math.min(x, dragStart));
+      selection.setAttribute('width', Math.abs(x - dragStart));
+      selection.setAttribute('visibility', 'visible');
+    }
+    // Closest point of every series to the cursor
+    const rows = [];
+    let guide = null;
+    for (const s of series) {
+      let best = null;
+      for (const p of s.points) {
+        if (best === null || Math.abs(toX(p[0]) - x) < Math.abs(toX(best[0]) - x)) best = p;
+      }
+      if (best === null) continue;
+      guide = guide === null ? best[0] : guide;
+      rows.push(`<i style="background:${s.color}"></i>${escape(s.name)}: ${fmtNum(best[1])}`);
+    }
+    if (guide === null || x < M.left || x > W - M.right) {
+      tip.style.display = 'none';
+      svg.querySelector('.guide').setAttribute('visibility', 'hidden');
+      return;
+    }
+    const line = svg.querySelector('.guide');
+    line.setAttribute('x1', toX(guide));
+    line.setAttribute('x2', toX(guide));
+    line.setAttribute('visibility', 'visible');
+    const heading = graph.time_axis ? fmtDate(guide) : `${escape(graph.x_axis)}: ${fmtNum(guide)}`;
+    tip.innerHTML = `<b>${heading}</b><br>${rows.join('<br>')}`;
+    tip.style.display = 'block';
+    const bounds = box.getBoundingClientRect();
+    tip.style.left = `${event.clientX - bounds.left + 12}px`;
+    tip.style.top = `${event.clientY - bounds.top + 12}px`;
+  });
+  svg.addEventListener('mouseleave', () => {
+    tip.style.display = 'none';
+    svg.querySelector('.guide').setAttribute('visibility', 'hidden');
+  });
+  window.addEventListener('mouseup', event => {
+    if (dragStart === null) return;
+    const x = toSvgX(event);
+    if (Math.abs(x - dragStart) > 5) {
+      const low = fromX(Math.max(Math.min(x, dragStart), M.left));
+      const high = fromX(Math.min(Math.max(x, dragStart), W - M.right));
+      if (high > low) view = [low, high];
+    }
+    dragStart = null;
+    render();
+  });
+  svg.addEventListener('dblclick', () => {
+    view = full.slice();
+    render();
+  });
+  render();
+}
+
+function drawBars(box, panel) {
+  if (!panel.bars.length) {
+    box.append('no data to plot');
+    return;
+  }
+  const svg = el('svg', { viewBox: `0 0 ${W} ${H}`, class: 'chart' }, box);
+  const left = 220;
+  const max = Math.max(...panel.bars.map(b => b[1])) || 1;
+  const height = (H - M.top - M.bottom) / panel.bars.length;
+  panel.bars.forEach(([label, value], i) => {
+    const y = M.top + i * height;
+    const width = Math.max(value, 0) / max * (W - left - M.right);
+    const bar = el('rect', { x: left, y: y + height * 0.1, width, height: height * 0.8, fill: COLORS[2], class: 'bar' }, svg);
+    el('title', {}, bar).textContent = `${label}: ${fmtNum(value)}`;
+    const short = label.length > 32 ? label.slice(0, 31) + '…' : label;
+    text(svg, { x: left - 6, y: y + height / 2 + 4, 'text-anchor': 'end' }, short);
+    text(svg, { x: left + width + 4, y: y + height / 2 + 4 }, fmtNum(value));
+  });
+  el('line', { x1: left, x2: left, y1: M.top, y2: H - M.bottom, class: 'axis' }, svg);
+  text(svg, { x: left + (W - left - M.right) / 2, y: H - 4, 'text-anchor': 'middle', class: 'label' }, panel.x_axis);
+}
+
+const panels = JSON.parse(document.getElementById('panels').textContent);
+const charts = document.getElementById('charts');
+for (const panel of panels) {
+  const box = document.createElement('div');
+  box.className = 'panel';
+  const title = document.createElement('h3');
+  title.textContent = panel.title;
+  box.appendChild(title);
+  charts.appendChild(box);
+  if (panel.kind === 'bars') drawBars(box, panel);
+  else drawLines(box, panel);
+}
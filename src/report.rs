@@ -0,0 +1,115 @@
+use std::{
+    fs::{create_dir_all, write},
+    path::{Path, PathBuf},
+};
+
+use chrono::SecondsFormat;
+
+use crate::{
+    plot::Panel,
+    table::{self, TableFormat, TableOptions, TABLE_COLUMNS},
+    LogData,
+};
+
+/// Draws the embedded panels, kept in its own file so it can be edited as javascript
+const REPORT_SCRIPT: &str = include_str!("report.js");
+
+const REPORT_STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.6em; }
+thead th { background: #f0f0f0; position: sticky; top: 0; }
+.hint { color: #666; }
+#charts { display: flex; flex-wrap: wrap; gap: 1em; }
+.panel { position: relative; width: 640px; }
+.panel h3 { margin: 0.3em 0; }
+.chart { width: 640px; height: 360px; user-select: none; font-size: 11px; }
+.chart .grid { stroke: #eee; }
+.chart .axis { stroke: #444; }
+.chart .label { font-size: 13px; }
+.chart .guide { stroke: #888; stroke-dasharray: 3 3; }
+.chart .selection { fill: rgba(67, 99, 216, 0.15); }
+.chart .bar:hover { opacity: 0.7; }
+.legend span { margin-right: 1em; font-size: 0.9em; }
+.legend i, .tip i { display: inline-block; width: 0.8em; height: 0.8em; margin-right: 0.3em; }
+.tip { display: none; position: absolute; pointer-events: none; background: #fff;
+       border: 1px solid #aaa; padding: 0.3em 0.5em; font-size: 0.85em; white-space: nowrap; }
+.scroll { max-height: 40em; overflow: auto; }
+";
+
+/// Every column of the data, shown without units so no values are rounded away
+fn get_table_options() -> TableOptions {
+    TableOptions {
+        columns: TABLE_COLUMNS.iter().map(|x| x.to_string()).collect(),
+        sort: None,
+        descending: false,
+        totals: false,
+        human: false,
+        format: TableFormat::Html,
+    }
+}
+
+/// Self contained html page with the summary, per bucket tables and interactive charts
+pub(crate) fn get_report(
+    time: &str,
+    total: &LogData,
+    buckets: &[LogData],
+    panels: &[Panel],
+) -> String {
+    let options = get_table_options();
+    // Keeps the data from closing the script tag it is embedded in
+    let panels = serde_json::to_string(panels)
+        .expect("unable to write json")
+        .replace("</", "<\\/");
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Log Report</title>
+<style>{REPORT_STYLE}</style>
+</head>
+<body>
+<h1>Log Report</h1>
+<p>{start} to {end} in {count} buckets of one {time}</p>
+<h2>Summary</h2>
+{summary}
+<h2>Charts</h2>
+<p class=\"hint\">Hover a chart to see its values, drag across it to zoom in and double click to zoom back out.</p>
+<div id=\"charts\"></div>
+<h2>Buckets</h2>
+<div class=\"scroll\">
+{buckets}
+</div>
+<script type=\"application/json\" id=\"panels\">{panels}</script>
+<script>
+{REPORT_SCRIPT}
+</script>
+</body>
+</html>
+",
+        start = total.start_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        end = total.end_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        count = buckets.len(),
+        summary = table::get_table(&options, std::slice::from_ref(total), total),
+        buckets = table::get_table(&options, buckets, total),
+    )
+}
+
+/// Writes the report, creating the parent directories when needed
+pub(crate) fn save_report(path: &Path, report: &str) {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent).expect("unable to create the report directory");
+    }
+    write(path, report).expect("unable to write the report");
+    println!("Report saved to {}", path.display());
+}
+
+/// Checks that a report output path has the .html extension
+pub(crate) fn parse_report_path(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    match path.extension().and_then(|x| x.to_str()) {
+        Some("html") => Ok(path),
+        _ => Err("reports can only be saved as .html".to_string()),
+    }
+}
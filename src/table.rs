@@ -1,8 +1,476 @@
-pub(crate) fn get_line_similarity(line_one: &Vec<(f64, f64)>, line_two: &Vec<(f64, f64)>) {
-    let mut total = 0.0;
-    let length = line_one.clone().len() as f64;
-    for i in 0..line_one.len() {
-        total += (line_one[i].1 - line_two[i].1).abs()
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+
+use crate::{
+    util::{
+        backtest::Backtest,
+        fit::GoodnessOfFit,
+        log_analyzer::Dimension,
+        models::{FittedModel, MODELTYPES},
+        top::{SpaceSaving, TopBy},
+    },
+    Expression, LogData,
+};
+
+/// Every plain column, any metric expression can be used as a column as well
+pub(crate) const TABLE_COLUMNS: [&str; 15] = [
+    "start_time",
+    "end_time",
+    "time",
+    "users",
+    "sessions",
+    "total_bytes",
+    "avg_bytes",
+    "hits",
+    "errors",
+    "atbl",
+    "atbe",
+    "2xx",
+    "3xx",
+    "4xx",
+    "5xx",
+];
+
+/// Checks that a column is one of the dates or a metric expression
+pub(crate) fn parse_column(column: &str) -> Result<String, String> {
+    match column.trim() {
+        "start_time" | "end_time" => Ok(column.trim().to_string()),
+        _ => Expression::parse(column).map(|x| x.to_string()),
     }
-    println!("Avg Distance of points: {}", total / length);
 }
+
+/// Ways a table can be rendered
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TableFormat {
+    Terminal,
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct TableOptions {
+    pub(crate) columns: Vec<String>,
+    pub(crate) sort: Option<String>,
+    pub(crate) descending: bool,
+    pub(crate) totals: bool,
+    pub(crate) human: bool,
+    pub(crate) format: TableFormat,
+}
+
+/// Formats a count with a k/M/G suffix
+pub(crate) fn human_count(value: f64) -> String {
+    let units = ["", "k", "M", "G"];
+    let mut value = value;
+    let mut unit = 0;
+    while value.abs() >= 1000.0 && unit < units.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{}", value),
+        _ => format!("{:.1}{}", value, units[unit]),
+    }
+}
+
+/// Formats a size stored in kilobytes with the largest fitting unit
+pub(crate) fn human_bytes(kilobytes: f64) -> String {
+    let units = ["B", "kB", "MB", "GB", "TB"];
+    let mut value = kilobytes * 1000.0;
+    let mut unit = 0;
+    while value.abs() >= 1000.0 && unit < units.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, units[unit])
+}
+
+/// Formats seconds as the two largest units of days, hours, minutes and seconds
+fn human_duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    let parts = [
+        (total / 86400, "d"),
+        (total % 86400 / 3600, "h"),
+        (total % 3600 / 60, "m"),
+        (total % 60, "s"),
+    ];
+    let parts: Vec<String> = parts
+        .into_iter()
+        .skip_while(|(value, _)| *value == 0)
+        .take(2)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect();
+    match parts.len() {
+        0 => "0s".to_string(),
+        _ => parts.join(" "),
+    }
+}
+
+/// Expression of a numeric column, the columns are checked when they are parsed
+fn get_expression(column: &str) -> Expression {
+    Expression::parse(column).expect("column is not an expression")
+}
+
+fn format_cell(data: &LogData, column: &str, human: bool) -> String {
+    match column {
+        "start_time" => data.start_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "end_time" => data.end_time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        _ => {
+            let expression = get_expression(column);
+            let Some(value) = expression.evaluate(data) else {
+                // The expression divides by zero for this bucket
+                return "-".to_string();
+            };
+            // Derived values have no unit so they are always shown as plain numbers
+            match (expression.get_metric().map(|x| x.get_unit()), human) {
+                (Some("count"), true) => human_count(value),
+                (Some("count"), false) => value.to_string(),
+                (Some("kB"), true) => human_bytes(value),
+                (Some("seconds"), true) => human_duration(value),
+                _ => format!("{:.3}", value),
+            }
+        }
+    }
+}
+
+/// Dates, groups and dimension values are left aligned while every other column holds numbers
+fn is_numeric(column: &str) -> bool {
+    !column.ends_with("_time")
+        && !["group", "model"].contains(&column)
+        && Dimension::from_str(column, false).is_err()
+}
+
+fn render_terminal(
+    columns: &[String],
+    rows: &[Vec<String>],
+    totals: Option<&Vec<String>>,
+) -> String {
+    let mut widths: Vec<usize> = columns.iter().map(|x| x.len()).collect();
+    for row in rows.iter().chain(totals) {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.len());
+        }
+    }
+    let format_row = |row: &Vec<String>| -> String {
+        row.iter()
+            .enumerate()
+            .map(|(i, cell)| match is_numeric(&columns[i]) {
+                true => format!("{:>width$}", cell, width = widths[i]),
+                false => format!("{:<width$}", cell, width = widths[i]),
+            })
+            .collect::<Vec<String>>()
+            .join("  ")
+    };
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<String>>()
+        .join("  ");
+    let mut table = vec![format_row(&columns.to_vec()), separator.clone()];
+    table.extend(rows.iter().map(format_row));
+    if let Some(totals) = totals {
+        table.push(separator);
+        table.push(format_row(totals));
+    }
+    table.join("\n")
+}
+
+fn render_markdown(
+    columns: &[String],
+    rows: &[Vec<String>],
+    totals: Option<&Vec<String>>,
+) -> String {
+    let format_row = |row: &[String]| {
+        // A pipe inside a cell would start a new column
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let alignment: Vec<String> = columns
+        .iter()
+        .map(|column| match is_numeric(column) {
+            true => "---:".to_string(),
+            false => ":---".to_string(),
+        })
+        .collect();
+    let mut table = vec![format_row(columns), format_row(&alignment)];
+    table.extend(rows.iter().map(|row| format_row(row)));
+    if let Some(totals) = totals {
+        // Markdown has no footer so the totals are bolded instead
+        let totals: Vec<String> = totals.iter().map(|x| format!("**{}**", x)).collect();
+        table.push(format_row(&totals));
+    }
+    table.join("\n")
+}
+
+/// Replaces the characters that would be read as markup, since cells can hold logged paths
+fn escape_html(cell: &str) -> String {
+    cell.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(columns: &[String], rows: &[Vec<String>], totals: Option<&Vec<String>>) -> String {
+    let format_row = |row: &[String], tag: &str| -> String {
+        let cells: String = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let cell = escape_html(cell);
+                match is_numeric(&columns[i]) {
+                    true => format!("<{tag} style=\"text-align:right\">{cell}</{tag}>"),
+                    false => format!("<{tag}>{cell}</{tag}>"),
+                }
+            })
+            .collect();
+        format!("    <tr>{}</tr>\n", cells)
+    };
+    let mut table = String::from("<table>\n  <thead>\n");
+    table += &format_row(columns, "th");
+    table += "  </thead>\n  <tbody>\n";
+    for row in rows {
+        table += &format_row(row, "td");
+    }
+    table += "  </tbody>\n";
+    if let Some(totals) = totals {
+        table += "  <tfoot>\n";
+        table += &format_row(totals, "th");
+        table += "  </tfoot>\n";
+    }
+    table += "</table>";
+    table
+}
+
+/// Indexes of the rows in the order of the sort column
+fn get_order(options: &TableOptions, data: &[&LogData]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    if let Some(column) = &options.sort {
+        match column.as_str() {
+            "start_time" | "end_time" => order.sort_by_key(|i| data[*i].start_time),
+            _ => {
+                let expression = get_expression(column);
+                // Rows that divide by zero sort below every value
+                let get_value =
+                    |i: &usize| expression.evaluate(data[*i]).unwrap_or(f64::NEG_INFINITY);
+                order.sort_by(|a, b| get_value(a).total_cmp(&get_value(b)))
+            }
+        }
+        if options.descending {
+            order.reverse();
+        }
+    }
+    order
+}
+
+fn get_row(options: &TableOptions, data: &LogData) -> Vec<String> {
+    options
+        .columns
+        .iter()
+        .map(|column| format_cell(data, column, options.human))
+        .collect()
+}
+
+fn render(
+    format: TableFormat,
+    columns: &[String],
+    rows: &[Vec<String>],
+    totals: Option<&Vec<String>>,
+) -> String {
+    match format {
+        TableFormat::Terminal => render_terminal(columns, rows, totals),
+        TableFormat::Markdown => render_markdown(columns, rows, totals),
+        TableFormat::Html => render_html(columns, rows, totals),
+    }
+}
+
+/// Renders the per bucket log data as a table with an optional totals row
+pub(crate) fn get_table(options: &TableOptions, buckets: &[LogData], total: &LogData) -> String {
+    let buckets: Vec<&LogData> = buckets.iter().collect();
+    let rows: Vec<Vec<String>> = get_order(options, &buckets)
+        .into_iter()
+        .map(|i| get_row(options, buckets[i]))
+        .collect();
+    let totals = match options.totals {
+        true => {
+            let mut totals = get_row(options, total);
+            // Labels the row when it starts with a date instead of a total
+            if !is_numeric(&options.columns[0]) {
+                totals[0] = "total".to_string();
+            }
+            Some(totals)
+        }
+        false => None,
+    };
+    render(options.format, &options.columns, &rows, totals.as_ref())
+}
+
+/// Renders the log data of each group as a row labeled with the group
+pub(crate) fn get_group_table(
+    options: &TableOptions,
+    groups: &[(String, LogData)],
+    total: &LogData,
+) -> String {
+    let data: Vec<&LogData> = groups.iter().map(|(_, data)| data).collect();
+    let with_label = |label: &str, data: &LogData| -> Vec<String> {
+        let mut row = vec![label.to_string()];
+        row.extend(get_row(options, data));
+        row
+    };
+    let rows: Vec<Vec<String>> = get_order(options, &data)
+        .into_iter()
+        .map(|i| with_label(&groups[i].0, data[i]))
+        .collect();
+    let totals = match options.totals {
+        true => Some(with_label("total", total)),
+        false => None,
+    };
+    let mut columns = vec!["group".to_string()];
+    columns.extend(options.columns.iter().cloned());
+    render(options.format, &columns, &rows, totals.as_ref())
+}
+
+/// Renders the largest values of a dimension with their share of the total
+pub(crate) fn get_top_table(
+    format: TableFormat,
+    field: Dimension,
+    by: TopBy,
+    counts: &SpaceSaving,
+    count: usize,
+) -> String {
+    let field = field.to_possible_value().unwrap().get_name().to_string();
+    let by_name = by.to_possible_value().unwrap().get_name().to_string();
+    let mut columns = vec!["rank".to_string(), field, by_name, "share".to_string()];
+    if counts.approximate {
+        columns.push("max_error".to_string());
+    }
+    let format_value = |value: u64| match by {
+        TopBy::Bytes => human_bytes(value as f64 / 1000.0),
+        _ => value.to_string(),
+    };
+    let rows: Vec<Vec<String>> = counts
+        .get_top(count)
+        .into_iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let mut row = vec![
+                (i + 1).to_string(),
+                entry.key,
+                format_value(entry.count),
+                format!("{:.1}%", entry.count as f64 / counts.total as f64 * 100.0),
+            ];
+            if counts.approximate {
+                row.push(format_value(entry.error));
+            }
+            row
+        })
+        .collect();
+    render(format, &columns, &rows, None)
+}
+
+/// Renders the goodness of fit of each model, already ranked best first
+pub(crate) fn get_model_table(models: &[(MODELTYPES, GoodnessOfFit)]) -> String {
+    let columns: Vec<String> = [
+        "rank",
+        "model",
+        "sse",
+        "mse",
+        "r_squared",
+        "ks",
+        "log_likelihood",
+        "aic",
+        "bic",
+    ]
+    .iter()
+    .map(|x| x.to_string())
+    .collect();
+    let rows: Vec<Vec<String>> = models
+        .iter()
+        .enumerate()
+        .map(|(i, (model, goodness))| {
+            vec![
+                (i + 1).to_string(),
+                model.to_string(),
+                format!("{:.3}", goodness.sse),
+                format!("{:.3}", goodness.mse),
+                format!("{:.4}", goodness.r_squared),
+                format!("{:.4}", goodness.ks),
+                format!("{:.3}", goodness.log_likelihood),
+                format!("{:.3}", goodness.aic),
+                format!("{:.3}", goodness.bic),
+            ]
+        })
+        .collect();
+    render(TableFormat::Terminal, &columns, &rows, None)
+}
+
+/// Renders the failures predicted by the end of each period, given as its end time and time in
+/// the unit of the model, after the last bucket ends at `end` with `observed` failures so far
+pub(crate) fn get_forecast_table(
+    format: TableFormat,
+    fitted: &FittedModel,
+    observed: f64,
+    end: f64,
+    periods: &[(DateTime<Utc>, f64)],
+) -> String {
+    let mut columns: Vec<String> = vec!["period".to_string(), "end_time".to_string()];
+    columns.push("errors".to_string());
+    if fitted.covariance.is_some() {
+        columns.extend(["lower".to_string(), "upper".to_string()]);
+    }
+    columns.push("intensity".to_string());
+    let rows: Vec<Vec<String>> = periods
+        .iter()
+        .enumerate()
+        .map(|(i, (time, t))| {
+            let (expected, bounds) = fitted.get_prediction(end, *t);
+            let mut row = vec![
+                (i + 1).to_string(),
+                time.to_rfc3339_opts(SecondsFormat::Secs, true),
+                format!("{:.3}", observed + expected),
+            ];
+            if let Some((low, high)) = bounds {
+                row.extend([
+                    format!("{:.3}", observed + low),
+                    format!("{:.3}", observed + high),
+                ]);
+            }
+            row.push(format!("{:.3}", fitted.get_intensity(*t)));
+            row
+        })
+        .collect();
+    render(format, &columns, &rows, None)
+}
+
+/// Renders how well each model predicted the buckets it wasn't fit to, already ranked best first
+pub(crate) fn get_backtest_table(format: TableFormat, models: &[(MODELTYPES, Backtest)]) -> String {
+    let columns: Vec<String> = [
+        "rank",
+        "model",
+        "origins",
+        "failed",
+        "relative_error",
+        "absolute_error",
+        "mean_log_likelihood",
+    ]
+    .iter()
+    .map(|x| x.to_string())
+    .collect();
+    let rows: Vec<Vec<String>> = models
+        .iter()
+        .enumerate()
+        .map(|(i, (model, backtest))| {
+            vec![
+                (i + 1).to_string(),
+                model.to_string(),
+                backtest.origins.to_string(),
+                backtest.failed.to_string(),
+                format!("{:.2}%", backtest.relative_error * 100.0),
+                format!("{:.3}", backtest.absolute_error),
+                format!("{:.3}", backtest.mean_log_likelihood),
+            ]
+        })
+        .collect();
+    render(format, &columns, &rows, None)
+}
+
+//let (stats, log_count) = count_status_code(log_data);
+//println!("{:?}\n{}", stats, log_count)
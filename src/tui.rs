@@ -0,0 +1,366 @@
+use std::io;
+
+use chrono::{DateTime, TimeZone, Utc};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Bar, BarChart, BarGroup, Block, Paragraph, Sparkline},
+    DefaultTerminal, Frame,
+};
+
+use crate::util::{
+    log::Log,
+    log_analyzer::{LogAnalyzer, TIME_RANGES},
+    log_data::{LogData, Metric},
+    to_time,
+};
+
+/// Metrics that can be drawn, the first four also get a sparkline
+const TUI_METRICS: [Metric; 8] = [
+    Metric::Hits,
+    Metric::Errors,
+    Metric::Users,
+    Metric::TotalBytes,
+    Metric::Sessions,
+    Metric::AvgBytes,
+    Metric::Atbl,
+    Metric::Atbe,
+];
+
+/// Which responses are kept by their status code
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StatusFilter {
+    All,
+    Errors,
+    Successes,
+}
+
+impl StatusFilter {
+    fn next(self) -> StatusFilter {
+        match self {
+            StatusFilter::All => StatusFilter::Errors,
+            StatusFilter::Errors => StatusFilter::Successes,
+            StatusFilter::Successes => StatusFilter::All,
+        }
+    }
+
+    fn keeps(self, log: &Log) -> bool {
+        match self {
+            StatusFilter::All => true,
+            StatusFilter::Errors => log.status_code.0 >= 400,
+            StatusFilter::Successes => log.status_code.0 < 400,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            StatusFilter::All => "all",
+            StatusFilter::Errors => "errors",
+            StatusFilter::Successes => "successes",
+        }
+    }
+}
+
+/// Logs loaded once along with everything the user can change with keystrokes
+struct App {
+    logs: Vec<Log>,
+    /// Unix timestamp of each log so the time window doesn't reparse dates
+    times: Vec<i64>,
+    bounds: (i64, i64),
+    window: (i64, i64),
+    range: usize,
+    metric: usize,
+    status: StatusFilter,
+    path_filter: String,
+    /// Path filter being typed, applied once enter is pressed
+    editing: Option<String>,
+    buckets: Vec<LogData>,
+    total: Option<LogData>,
+}
+
+fn to_date(timestamp: i64) -> DateTime<Utc> {
+    Utc.timestamp_opt(timestamp, 0).unwrap()
+}
+
+impl App {
+    fn new(logs: Vec<Log>, range: &str) -> App {
+        let times: Vec<i64> = logs
+            .iter()
+            .map(|log| to_time(&log.clone().get_parsed_date()) as i64)
+            .collect();
+        let bounds = (*times.iter().min().unwrap(), *times.iter().max().unwrap());
+        let mut app = App {
+            logs,
+            times,
+            bounds,
+            window: bounds,
+            range: TIME_RANGES
+                .iter()
+                .position(|(name, _)| *name == range)
+                .unwrap_or(3),
+            metric: 0,
+            status: StatusFilter::All,
+            path_filter: String::new(),
+            editing: None,
+            buckets: Vec::new(),
+            total: None,
+        };
+        app.refresh();
+        app
+    }
+
+    /// Buckets the logs that pass the filters and fall in the time window
+    fn refresh(&mut self) {
+        let logs: Vec<Log> = self
+            .logs
+            .iter()
+            .zip(&self.times)
+            .filter(|(_, time)| (self.window.0..=self.window.1).contains(*time))
+            .filter(|(log, _)| self.status.keeps(log))
+            .filter(|(log, _)| log.get_path().contains(&self.path_filter))
+            .map(|(log, _)| log.clone())
+            .collect();
+        if logs.is_empty() {
+            self.buckets = Vec::new();
+            self.total = None;
+            return;
+        }
+        let (name, time_multi) = TIME_RANGES[self.range];
+        let (mut buckets, total) = LogAnalyzer { logs, time_multi }.get_data(name);
+        buckets.sort_by_key(|data| data.start_time);
+        self.buckets = buckets;
+        self.total = Some(total);
+    }
+
+    /// Moves the time window by a quarter of its length without leaving the logs
+    fn pan(&mut self, forward: bool) {
+        let span = self.window.1 - self.window.0;
+        let step = (span / 4).max(1);
+        let start = match forward {
+            true => (self.window.0 + step).min(self.bounds.1 - span),
+            false => (self.window.0 - step).max(self.bounds.0),
+        };
+        self.window = (start, start + span);
+    }
+
+    /// Halves or doubles the time window around its center
+    fn zoom(&mut self, zoom_in: bool) {
+        let center = self.window.0 + (self.window.1 - self.window.0) / 2;
+        let span = self.window.1 - self.window.0;
+        let half = match zoom_in {
+            true => (span / 4).max(TIME_RANGES[self.range].1),
+            false => span,
+        };
+        self.window = (
+            (center - half).max(self.bounds.0),
+            (center + half).min(self.bounds.1),
+        );
+    }
+
+    /// Applies a key press, returning false once the user quits
+    fn handle_key(&mut self, key: KeyCode) -> bool {
+        if let Some(filter) = &mut self.editing {
+            match key {
+                KeyCode::Enter => {
+                    self.path_filter = self.editing.take().unwrap();
+                    self.refresh();
+                }
+                KeyCode::Esc => self.editing = None,
+                KeyCode::Backspace => {
+                    filter.pop();
+                }
+                KeyCode::Char(c) => filter.push(c),
+                _ => {}
+            }
+            return true;
+        }
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('b') => self.range = (self.range + 1) % TIME_RANGES.len(),
+            KeyCode::Char('B') => {
+                self.range = (self.range + TIME_RANGES.len() - 1) % TIME_RANGES.len()
+            }
+            KeyCode::Char('m') => self.metric = (self.metric + 1) % TUI_METRICS.len(),
+            KeyCode::Char('M') => {
+                self.metric = (self.metric + TUI_METRICS.len() - 1) % TUI_METRICS.len()
+            }
+            KeyCode::Char('s') => self.status = self.status.next(),
+            KeyCode::Char('/') => self.editing = Some(self.path_filter.clone()),
+            KeyCode::Left => self.pan(false),
+            KeyCode::Right => self.pan(true),
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zoom(true),
+            KeyCode::Char('-') => self.zoom(false),
+            KeyCode::Char('r') => {
+                self.window = self.bounds;
+                self.status = StatusFilter::All;
+                self.path_filter = String::new();
+            }
+            _ => return true,
+        }
+        // Metrics are read from the existing buckets, everything else changes them
+        if !matches!(key, KeyCode::Char('m') | KeyCode::Char('M')) {
+            self.refresh();
+        }
+        true
+    }
+
+    fn get_values(&self, metric: Metric) -> Vec<f64> {
+        self.buckets
+            .iter()
+            .map(|data| data.clone().get_data(metric))
+            .collect()
+    }
+
+    /// Short bucket label that only keeps the parts of the date that change
+    fn get_label(&self, data: &LogData) -> String {
+        let format = match TIME_RANGES[self.range].0 {
+            "sec" | "min" => "%H:%M:%S",
+            "hour" => "%d %Hh",
+            "day" => "%m-%d",
+            "month" => "%Y-%m",
+            _ => "%Y",
+        };
+        data.start_time.format(format).to_string()
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, body, sparklines, footer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(5),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [chart, summary] =
+            Layout::horizontal([Constraint::Percentage(70), Constraint::Percentage(30)])
+                .areas(body);
+
+        let path_filter = match &self.editing {
+            Some(filter) => format!("{}_", filter),
+            None => self.path_filter.clone(),
+        };
+        let status = format!(
+            "bucket: {}   metric: {}   status: {}   path: {}   window: {} to {}",
+            TIME_RANGES[self.range].0,
+            TUI_METRICS[self.metric],
+            self.status.name(),
+            path_filter,
+            to_date(self.window.0).format("%Y-%m-%d %H:%M:%S"),
+            to_date(self.window.1).format("%Y-%m-%d %H:%M:%S"),
+        );
+        frame.render_widget(
+            Paragraph::new(status).block(Block::bordered().title(" Log Analyzer ")),
+            header,
+        );
+
+        self.draw_bars(frame, chart);
+        let summary_text = match &self.total {
+            Some(total) => total.to_string(),
+            None => "No logs match the filters".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(summary_text).block(Block::bordered().title(" Summary ")),
+            summary,
+        );
+
+        let areas = Layout::horizontal([Constraint::Ratio(1, 4); 4]).split(sparklines);
+        for (area, metric) in areas.iter().zip(TUI_METRICS) {
+            // Sparklines only take whole numbers so values are scaled to the largest one
+            let values = self.get_values(metric);
+            let max = values.iter().cloned().fold(0.0, f64::max);
+            let scaled: Vec<u64> = values
+                .iter()
+                .map(|x| match max > 0.0 {
+                    true => (x / max * 100.0) as u64,
+                    false => 0,
+                })
+                .collect();
+            frame.render_widget(
+                Sparkline::default()
+                    .block(Block::bordered().title(format!(" {} ", metric)))
+                    .data(&scaled)
+                    .style(Style::default().fg(Color::Cyan)),
+                *area,
+            );
+        }
+
+        let keys = "b/B bucket  m/M metric  s status  / path filter  \u{2190}/\u{2192} pan  +/- zoom  r reset  q quit";
+        frame.render_widget(Line::from(keys).dim(), footer);
+    }
+
+    /// Bar per bucket of the chosen metric, keeping the latest buckets that fit
+    fn draw_bars(&self, frame: &mut Frame, area: Rect) {
+        let metric = TUI_METRICS[self.metric];
+        let labels: Vec<String> = self.buckets.iter().map(|x| self.get_label(x)).collect();
+        let width = labels.iter().map(|x| x.len()).max().unwrap_or(1).max(5) as u16;
+        let fits = (area.width.saturating_sub(2) / (width + 1)).max(1) as usize;
+        let values = self.get_values(metric);
+        let skip = values.len().saturating_sub(fits);
+        let max = values.iter().cloned().fold(0.0, f64::max);
+        // Bars only take whole numbers so the value is shown as text instead
+        let bars: Vec<Bar> = values
+            .iter()
+            .zip(&labels)
+            .skip(skip)
+            .map(|(value, label)| {
+                let height = match max > 0.0 {
+                    true => (value / max * 1000.0) as u64,
+                    false => 0,
+                };
+                Bar::default()
+                    .value(height)
+                    .text_value(format_value(*value))
+                    .label(Line::from(label.clone()))
+            })
+            .collect();
+        let title = format!(
+            " {} per {} ({} of {} buckets) ",
+            metric,
+            TIME_RANGES[self.range].0,
+            bars.len(),
+            values.len()
+        );
+        frame.render_widget(
+            BarChart::default()
+                .block(Block::bordered().title(title))
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(width)
+                .bar_gap(1)
+                .bar_style(Style::default().fg(Color::Green))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Green)),
+            area,
+        );
+    }
+}
+
+/// Value short enough to fit on a bar
+fn format_value(value: f64) -> String {
+    match value.abs() {
+        x if x >= 1e6 => format!("{:.1}M", value / 1e6),
+        x if x >= 1e4 => format!("{:.0}k", value / 1e3),
+        x if x >= 100.0 || x == value.trunc().abs() => format!("{:.0}", value),
+        _ => format!("{:.2}", value),
+    }
+}
+
+fn run_app(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !app.handle_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Opens a full screen dashboard over the logs until the user quits
+pub(crate) fn run(logs: Vec<Log>, range: &str) -> io::Result<()> {
+    let app = App::new(logs, range);
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, app);
+    ratatui::restore();
+    result
+}
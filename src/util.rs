@@ -1,5 +1,4 @@
 use chrono::{TimeZone, Utc};
-use indexmap::IndexMap;
 use log::Log;
 use std::{
     collections::{HashMap, HashSet},
@@ -7,11 +6,17 @@ use std::{
     path::Path,
     str,
 };
+use unique::UniqueCounter;
 
+pub(crate) mod backtest;
+pub(crate) mod expression;
+pub(crate) mod fit;
 pub(super) mod log;
 pub(crate) mod log_analyzer;
 pub(super) mod log_data;
 pub(crate) mod models;
+pub(crate) mod top;
+pub(crate) mod unique;
 
 pub(crate) fn get_byte_info(logs: Vec<Log>) -> (f64, f64) {
     let log_count = logs.len();
@@ -19,25 +24,23 @@ pub(crate) fn get_byte_info(logs: Vec<Log>) -> (f64, f64) {
     for log in logs {
         total_bytes += log.size;
     }
-    let average = total_bytes as f64 / log_count as f64;
-    return (total_bytes, average);
+    let average = total_bytes / log_count as f64;
+    (total_bytes, average)
 }
 
-pub(crate) fn get_users(logs: Vec<Log>) -> (IndexMap<std::string::String, Vec<Vec<u32>>>, usize) {
-    let mut users: IndexMap<String, Vec<Vec<u32>>> = IndexMap::new();
-    let mut count = 0 as usize;
+/// Counts the distinct IPs in a sketch that can be merged with the counts of other logs
+pub(crate) fn count_users(logs: &[Log]) -> UniqueCounter {
+    let mut users = UniqueCounter::default();
     for log in logs {
-        let ip = log.clone().ip;
-        if let Some(user) = users.get_mut(&ip) {
-            user.push(log.get_parsed_date());
-        } else {
-            users.insert(ip.clone(), Vec::from([log.get_parsed_date()]));
-            count += 1
-        }
+        users.add(&log.ip);
     }
-    return (users, count);
+    users
 }
 pub(crate) fn get_avg_time(logs: Vec<Log>) -> f64 {
+    // Groups without any errors have no time between them
+    if logs.is_empty() {
+        return 0.0;
+    }
     let total_logs = logs.len() as i64;
     let range = logs.len() as i64 - 1;
     let mut avg_time = 0.0;
@@ -46,37 +49,35 @@ pub(crate) fn get_avg_time(logs: Vec<Log>) -> f64 {
         let time_two = <log::Log as Clone>::clone(&logs[i + 1]).get_parsed_date();
         avg_time += time_difference(to_time(&time_one), to_time(&time_two));
     }
-    return avg_time / total_logs as f64;
+    avg_time / total_logs as f64
 }
 /// Returns difference between two dates in seconds.
 pub(crate) fn time_difference(d1: f64, d2: f64) -> f64 {
     let difference = d2 - d1;
-    return difference.abs();
+    difference.abs()
 }
 
-pub(crate) fn to_time(d1: &Vec<u32>) -> f64 {
+pub(crate) fn to_time(d1: &[u32]) -> f64 {
     let time: i64 = Utc
         .with_ymd_and_hms(d1[0] as i32, d1[1], d1[2], d1[3], d1[4], d1[5])
         .unwrap()
         .timestamp();
 
-    return time as f64;
+    time as f64
 }
 
-pub(crate) fn get_sessions(sec: f64, users: IndexMap<String, Vec<Vec<u32>>>) -> u64 {
+/// Counts the visits of each IP, starting a new one after `sec` seconds without requests
+pub(crate) fn get_sessions(sec: f64, logs: &[Log]) -> u64 {
+    let mut last_seen: HashMap<&str, f64> = HashMap::new();
     let mut total_sessions: u64 = 0;
-    for user in users.values() {
-        total_sessions += 1;
-        let log_count = user.len();
-        for i in 0..log_count - 1 {
-            let st = to_time(&user[i]);
-            let difference = time_difference(st, to_time(&user[i + 1]));
-            if difference > sec {
-                total_sessions += 1
-            }
+    for log in logs {
+        let time = to_time(&log.clone().get_parsed_date());
+        match last_seen.insert(&log.ip, time) {
+            Some(last) if time_difference(last, time) <= sec => {}
+            _ => total_sessions += 1,
         }
     }
-    return total_sessions;
+    total_sessions
 }
 
 pub(crate) fn get_avg_group_time(logs: Vec<Log>) -> f64 {
@@ -86,7 +87,7 @@ pub(crate) fn get_avg_group_time(logs: Vec<Log>) -> f64 {
         total += to_time(&log.get_parsed_date());
         length += 1.0;
     }
-    return total / length;
+    total / length
 }
 
 /// Counts logs with errors and stores each error log in a vector.
@@ -100,20 +101,44 @@ pub(crate) fn count_errors(logs: Vec<Log>) -> (u64, Vec<Log>) {
             error_logs.push(log);
         }
     }
-    return (error_count, error_logs);
+    (error_count, error_logs)
+}
+
+/// Counts the 2xx, 3xx, 4xx and 5xx responses
+pub(crate) fn count_status_classes(logs: &[Log]) -> [u64; 4] {
+    let mut classes = [0; 4];
+    for log in logs {
+        if let 200..=599 = log.status_code.0 {
+            classes[(log.status_code.0 / 100 - 2) as usize] += 1;
+        }
+    }
+    classes
 }
 
 /// Takes path to a log file and reads and returns the results a String.
 pub(crate) fn read_directory(log_dir: &Path) -> String {
     let mut log_contents = String::new();
-    for logs in log_dir.read_dir().expect("read call failed") {
-        if let Ok(logs) = logs {
-            let file = logs.path();
-            let contents = read_to_string(&file).expect("unable to read file");
-            log_contents += &contents;
-        }
+    for logs in log_dir.read_dir().expect("read call failed").flatten() {
+        let file = logs.path();
+        let contents = read_to_string(&file).expect("unable to read file");
+        log_contents += &contents;
     }
-    return log_contents;
+    log_contents
+}
+
+/// Reads every log file in a directory along with the name of the file it came from
+pub(crate) fn read_log_files(log_dir: &Path) -> Vec<(String, String)> {
+    let mut log_files = Vec::new();
+    for logs in log_dir.read_dir().expect("read call failed").flatten() {
+        let file = logs.path();
+        let contents = read_to_string(&file).expect("unable to read file");
+        let source = file
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        log_files.push((source, contents));
+    }
+    log_files
 }
 
 /// Creates a hashmap of all HTTP status codes
@@ -122,21 +147,24 @@ pub(crate) fn create_http_hashmap() -> HashMap<String, String> {
     let code_file = Path::new("src/data/");
     let codes = read_directory(code_file);
     for code in codes.lines() {
-        let v: Vec<_> = code.split(",").collect();
+        let v: Vec<_> = code.split(',').collect();
         let key = v[0];
         let value = v[1];
         code_map.insert(key.to_string(), value.to_string());
     }
-    return code_map;
+    code_map
 }
 
 /// Allows the logs to split by spaces and separate each part properly and stores them as a Log struct
-pub(crate) fn parse_log(log_contents: String) -> Vec<log::Log> {
+pub(crate) fn parse_log(log_files: Vec<(String, String)>) -> Vec<log::Log> {
     let http_codes: HashMap<String, String> = create_http_hashmap();
     let mut current_logs: HashSet<String> = HashSet::new();
     let mut logs: Vec<log::Log> = Vec::new();
-    let contents: Vec<_> = log_contents.lines().collect();
-    for log in contents {
+    let contents: Vec<(&String, &str)> = log_files
+        .iter()
+        .flat_map(|(source, contents)| contents.lines().map(move |line| (source, line)))
+        .collect();
+    for (source, log) in contents {
         let log = make_log_parsable(log.to_string());
         let parsed_log: Vec<String> = log.split(" ").map(|x| x.to_string()).collect();
         match parsed_log.len() {
@@ -165,6 +193,9 @@ pub(crate) fn parse_log(log_contents: String) -> Vec<log::Log> {
                     request: parsed_log[4].to_owned(),
                     status_code: (status_code, description.to_owned()),
                     size: byte_size,
+                    referer: unquote(&parsed_log[7]),
+                    user_agent: unquote(&parsed_log[8]),
+                    source: source.to_owned(),
                 };
                 // Makes sure redundant logs aren't added to log list
                 let value_string = current_log.clone().get_values_string();
@@ -177,7 +208,12 @@ pub(crate) fn parse_log(log_contents: String) -> Vec<log::Log> {
             _ => continue,
         }
     }
-    return logs;
+    logs
+}
+
+/// Removes the quotes around a part of a log and restores the spaces within it
+fn unquote(part: &str) -> String {
+    part.trim_matches('"').replace('%', " ")
 }
 
 pub(crate) fn make_log_parsable(log: String) -> String {
@@ -203,5 +239,5 @@ pub(crate) fn make_log_parsable(log: String) -> String {
             }
         }
     }
-    return parsable_str;
+    parsable_str
 }
@@ -0,0 +1,96 @@
+use super::{
+    fit::{self, FitMethod},
+    models::{Exposure, Models, MODELTYPES},
+};
+use crate::LogData;
+
+/// How well a model predicted the errors of buckets it wasn't fit to
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Backtest {
+    /// Origins the model was fit at
+    pub(crate) origins: usize,
+    /// Origins the model couldn't be fit at, which are left out of the rest
+    pub(crate) failed: usize,
+    /// Mean distance of the predicted cumulative errors from the observed ones, relative to the
+    /// observed ones
+    pub(crate) relative_error: f64,
+    /// Mean distance of the predicted cumulative errors from the observed ones
+    pub(crate) absolute_error: f64,
+    /// Log-likelihood of the errors in the predicted buckets divided by their number, so models
+    /// that failed at some origins aren't favoured for having fewer buckets to predict
+    pub(crate) mean_log_likelihood: f64,
+}
+
+/// Fits the model to the buckets before each origin and predicts the errors in the `horizon`
+/// buckets after it, starting from the errors observed by the origin. Time is measured in
+/// units of `unit_seconds` when it is the exposure.
+pub(crate) fn backtest(
+    model: MODELTYPES,
+    fit: FitMethod,
+    log_data: &[LogData],
+    exposure: Exposure,
+    unit_seconds: f64,
+    origins: &[usize],
+    horizon: usize,
+) -> Result<Backtest, String> {
+    let intervals = exposure.get_intervals(log_data, unit_seconds);
+    let get_model = |length: usize| Models {
+        model,
+        fit,
+        total_errors: log_data[..length].iter().map(|x| x.errors as f64).sum(),
+        log_data: log_data[..length].to_vec(),
+        intervals: intervals[..length].to_vec(),
+        confidence: 0.95,
+    };
+    let full = get_model(log_data.len());
+    let times = full.get_times();
+    let counts = full.get_counts();
+    let mut relative_errors: Vec<f64> = Vec::new();
+    let mut absolute_errors: Vec<f64> = Vec::new();
+    let mut log_likelihood = 0.0;
+    let mut predicted = 0;
+    let mut failed = 0;
+    let mut last_error = String::new();
+    for origin in origins {
+        let params = match get_model(*origin).fit_params() {
+            Ok(fit) => fit.params,
+            Err(error) => {
+                failed += 1;
+                last_error = error;
+                continue;
+            }
+        };
+        let end = (origin + horizon).min(log_data.len());
+        let start = model.get_mean_value(&params, times[origin - 1]);
+        let increments: Vec<f64> = times[*origin..end]
+            .iter()
+            .map(|t| model.get_mean_value(&params, *t) - start)
+            .collect();
+        let future = &counts[*origin..end];
+        log_likelihood +=
+            fit::get_log_likelihood(future, &increments) - fit::get_log_factorials(future);
+        predicted += future.len();
+        let observed: f64 = counts[..*origin].iter().sum();
+        let mut actual = observed;
+        for (count, increment) in future.iter().zip(&increments) {
+            actual += count;
+            let error = (observed + increment - actual).abs();
+            absolute_errors.push(error);
+            // Nothing to be relative to before the first error
+            if actual > 0.0 {
+                relative_errors.push(error / actual);
+            }
+        }
+    }
+    if failed == origins.len() {
+        return Err(format!("unable to fit at any origin, {last_error}"));
+    }
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    Ok(Backtest {
+        origins: origins.len() - failed,
+        failed,
+        relative_error: mean(&relative_errors),
+        absolute_error: mean(&absolute_errors),
+        mean_log_likelihood: log_likelihood / predicted as f64,
+    })
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+
+use clap::ValueEnum;
+
+use super::log_data::{LogData, Metric};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Operator(char),
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Operator(operator) => write!(f, "{}", operator),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+        }
+    }
+}
+
+/// Parsed arithmetic over metrics
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Metric(Metric),
+    Negate(Box<Node>),
+    Binary(Box<Node>, char, Box<Node>),
+}
+
+/// Metric or arithmetic over metrics such as `errors/hits*100`, kept with the text it was parsed from
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Expression {
+    text: String,
+    node: Node,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' => {
+                chars.next();
+            }
+            '+' | '-' | '*' | '/' => {
+                tokens.push(Token::Operator(c));
+                chars.next();
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                chars.next();
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                chars.next();
+            }
+            _ if c.is_ascii_alphanumeric() || c == '_' || c == '.' => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                // Names such as 2xx start with a digit and words like nan or inf would parse
+                // as floats, so only words made of digits and dots are numbers
+                let is_number = word.chars().all(|c| c.is_ascii_digit() || c == '.');
+                match word.parse::<f64>() {
+                    Ok(number) if is_number => tokens.push(Token::Number(number)),
+                    _ => tokens.push(Token::Name(word)),
+                }
+            }
+            _ => return Err(format!("unexpected character '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the tokens, `*` and `/` binding tighter than `+` and `-`
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_sum(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_product()?;
+        while let Some(Token::Operator(operator @ ('+' | '-'))) = self.peek() {
+            let operator = *operator;
+            self.next();
+            node = Node::Binary(Box::new(node), operator, Box::new(self.parse_product()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_product(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_factor()?;
+        while let Some(Token::Operator(operator @ ('*' | '/'))) = self.peek() {
+            let operator = *operator;
+            self.next();
+            node = Node::Binary(Box::new(node), operator, Box::new(self.parse_factor()?));
+        }
+        Ok(node)
+    }
+
+    fn parse_factor(&mut self) -> Result<Node, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Node::Number(number)),
+            Some(Token::Name(name)) => match Metric::from_str(&name, false) {
+                Ok(metric) => Ok(Node::Metric(metric)),
+                Err(_) => Err(format!(
+                    "unknown metric '{}', expected one of {}",
+                    name,
+                    Metric::value_variants()
+                        .iter()
+                        .map(|x| x.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                )),
+            },
+            Some(Token::Operator('-')) => Ok(Node::Negate(Box::new(self.parse_factor()?))),
+            Some(Token::Open) => {
+                let node = self.parse_sum()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(node),
+                    _ => Err("missing closing parenthesis".to_string()),
+                }
+            }
+            Some(token) => Err(format!("unexpected '{}'", token)),
+            None => Err("expression ends too early".to_string()),
+        }
+    }
+}
+
+fn evaluate(node: &Node, data: &LogData) -> Option<f64> {
+    match node {
+        Node::Number(number) => Some(*number),
+        Node::Metric(metric) => Some(data.clone().get_data(*metric)),
+        Node::Negate(node) => Some(-evaluate(node, data)?),
+        Node::Binary(left, operator, right) => {
+            let (left, right) = (evaluate(left, data)?, evaluate(right, data)?);
+            match operator {
+                '+' => Some(left + right),
+                '-' => Some(left - right),
+                '*' => Some(left * right),
+                // Dividing by zero has no value rather than an infinite one
+                _ => match right == 0.0 {
+                    true => None,
+                    false => Some(left / right),
+                },
+            }
+        }
+    }
+}
+
+impl Expression {
+    pub(crate) fn parse(text: &str) -> Result<Expression, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        let node = parser.parse_sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("unexpected '{}'", token));
+        }
+        Ok(Expression {
+            text: text.trim().to_string(),
+            node,
+        })
+    }
+
+    /// Value for one bucket, `None` when the expression divides by zero
+    pub(crate) fn evaluate(&self, data: &LogData) -> Option<f64> {
+        evaluate(&self.node, data)
+    }
+
+    /// The metric when the expression is a single metric
+    pub(crate) fn get_metric(&self) -> Option<Metric> {
+        match self.node {
+            Node::Metric(metric) => Some(metric),
+            _ => None,
+        }
+    }
+}
+
+impl From<Metric> for Expression {
+    fn from(metric: Metric) -> Expression {
+        Expression {
+            text: metric.to_string(),
+            node: Node::Metric(metric),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
@@ -0,0 +1,710 @@
+use std::fmt;
+
+use clap::ValueEnum;
+
+/// How the parameters of a model are estimated from the errors in each bucket
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum FitMethod {
+    /// Maximum likelihood of the errors in each bucket
+    Mle,
+    /// Least squares distance to the cumulative errors
+    LeastSquares,
+}
+
+/// Expected failures by a time for the given parameters
+pub(crate) type MeanValue<'a> = &'a dyn Fn(&[f64], f64) -> f64;
+
+/// Step in the logarithm of a parameter used for numeric derivatives
+const STEP: f64 = 1e-4;
+
+/// Log-likelihood of the failures in each bucket given the expected failures by the end of each
+pub(crate) fn get_log_likelihood(counts: &[f64], mean_values: &[f64]) -> f64 {
+    let mut log_likelihood = -mean_values.last().unwrap();
+    let mut previous = 0.0;
+    for (count, mean_value) in counts.iter().zip(mean_values) {
+        let expected = mean_value - previous;
+        if *count > 0.0 {
+            if expected <= 0.0 {
+                return f64::NEG_INFINITY;
+            }
+            log_likelihood += count * expected.ln();
+        }
+        previous = *mean_value;
+    }
+    log_likelihood
+}
+
+/// Sum of the logarithm of the factorial of each count, which the likelihood of Poisson counts
+/// is divided by
+pub(crate) fn get_log_factorials(counts: &[f64]) -> f64 {
+    counts
+        .iter()
+        .map(|x| (2..=*x as u64).map(|x| (x as f64).ln()).sum::<f64>())
+        .sum()
+}
+
+/// Sum of squared distances between the cumulative failures and the expected failures
+pub(crate) fn get_sse(counts: &[f64], mean_values: &[f64]) -> f64 {
+    let mut cumulative = 0.0;
+    let mut sse = 0.0;
+    for (count, mean_value) in counts.iter().zip(mean_values) {
+        cumulative += count;
+        sse += f64::powi(cumulative - mean_value, 2);
+    }
+    sse
+}
+
+/// Value the fit makes as small as possible
+fn get_objective(
+    method: FitMethod,
+    mean_value: MeanValue,
+    params: &[f64],
+    times: &[f64],
+    counts: &[f64],
+) -> f64 {
+    let mean_values: Vec<f64> = times.iter().map(|t| mean_value(params, *t)).collect();
+    let value = match method {
+        FitMethod::Mle => -get_log_likelihood(counts, &mean_values),
+        FitMethod::LeastSquares => get_sse(counts, &mean_values),
+    };
+    // Parameters the model is undefined for are never picked
+    match value.is_nan() {
+        true => f64::INFINITY,
+        false => value,
+    }
+}
+
+/// Why a root could not be found
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum RootError {
+    /// The function has the same sign across the whole range searched
+    NoBracket {
+        low: f64,
+        high: f64,
+    },
+    /// The function has no value at a point it had to be evaluated at
+    NotFinite {
+        x: f64,
+    },
+    TooManyIterations {
+        iterations: usize,
+        residual: f64,
+    },
+}
+
+impl fmt::Display for RootError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RootError::NoBracket { low, high } => {
+                write!(f, "no sign change between {:.4} and {:.4}", low, high)
+            }
+            RootError::NotFinite { x } => write!(f, "undefined at {:.4}", x),
+            RootError::TooManyIterations {
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "no convergence after {} iterations, residual {:.3e}",
+                iterations, residual
+            ),
+        }
+    }
+}
+
+/// Root of a function along with how it was found
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Root {
+    pub(crate) x: f64,
+    pub(crate) iterations: usize,
+}
+
+/// Parameters of a fit along with how it settled
+#[derive(Clone, Debug)]
+pub(crate) struct Fit {
+    pub(crate) params: Vec<f64>,
+    /// Newton steps taken until the objective stopped improving
+    pub(crate) rounds: usize,
+    /// Root finding iterations spent sizing the steps
+    pub(crate) iterations: usize,
+    /// Largest slope of the objective relative to its value before the last round
+    pub(crate) residual: f64,
+}
+
+const MAX_ITERATIONS: usize = 100;
+const MAX_ROUNDS: usize = 200;
+const TOLERANCE: f64 = 1e-9;
+/// Largest logarithm of a parameter, past which it is treated as infinite or zero
+pub(crate) const LOG_LIMIT: f64 = 40.0;
+
+/// Newton's method kept between `low` and `high`, where `f` must change sign. Steps that
+/// leave the range or don't halve the residual switch to Brent's method for the rest.
+pub(crate) fn find_root(
+    f: &dyn Fn(f64) -> f64,
+    f_prime: &dyn Fn(f64) -> f64,
+    low: f64,
+    high: f64,
+) -> Result<Root, RootError> {
+    let (f_low, f_high) = (f(low), f(high));
+    for (x, value) in [(low, f_low), (high, f_high)] {
+        if !value.is_finite() {
+            return Err(RootError::NotFinite { x });
+        }
+        if value == 0.0 {
+            return Ok(Root { x, iterations: 0 });
+        }
+    }
+    if f_low.signum() == f_high.signum() {
+        return Err(RootError::NoBracket { low, high });
+    }
+    // Keeps the end where `f` is negative first
+    let (mut negative, mut positive) = match f_low < 0.0 {
+        true => (low, high),
+        false => (high, low),
+    };
+    let mut x = (low + high) / 2.0;
+    let mut previous = f_low.abs().max(f_high.abs());
+    for iteration in 1..=MAX_ITERATIONS {
+        let value = f(x);
+        if !value.is_finite() {
+            return Err(RootError::NotFinite { x });
+        }
+        match value < 0.0 {
+            true => negative = x,
+            false => positive = x,
+        }
+        let next = x - value / f_prime(x);
+        let inside = (next - negative) * (next - positive) < 0.0;
+        if !next.is_finite() || !inside || value.abs() > previous / 2.0 {
+            return brent(f, negative, positive, iteration);
+        }
+        if (next - x).abs() < TOLERANCE * (1.0 + x.abs()) {
+            return Ok(Root {
+                x: next,
+                iterations: iteration,
+            });
+        }
+        previous = value.abs();
+        x = next;
+    }
+    Err(RootError::TooManyIterations {
+        iterations: MAX_ITERATIONS,
+        residual: f(x).abs(),
+    })
+}
+
+/// Brent's method between two points where `f` has opposite signs, continuing the count
+/// of iterations already spent
+fn brent(
+    f: &dyn Fn(f64) -> f64,
+    mut a: f64,
+    mut b: f64,
+    iterations: usize,
+) -> Result<Root, RootError> {
+    let (mut fa, mut fb) = (f(a), f(b));
+    let (mut c, mut fc) = (b, fb);
+    let (mut d, mut e) = (b - a, b - a);
+    for iteration in iterations + 1..=iterations + MAX_ITERATIONS {
+        if fb.signum() == fc.signum() {
+            (c, fc) = (a, fa);
+            d = b - a;
+            e = d;
+        }
+        // Keeps `b` as the best guess
+        if fc.abs() < fb.abs() {
+            (a, fa) = (b, fb);
+            (b, fb) = (c, fc);
+            (c, fc) = (a, fa);
+        }
+        let tolerance = 2.0 * f64::EPSILON * b.abs() + TOLERANCE / 2.0;
+        let middle = (c - b) / 2.0;
+        if middle.abs() <= tolerance || fb == 0.0 {
+            return Ok(Root {
+                x: b,
+                iterations: iteration,
+            });
+        }
+        if e.abs() >= tolerance && fa.abs() > fb.abs() {
+            // Interpolates through the last points, falling back to bisection when it would
+            // step too far or too slowly
+            let s = fb / fa;
+            let (mut p, mut q) = match a == c {
+                true => (2.0 * middle * s, 1.0 - s),
+                false => {
+                    let (q, r) = (fa / fc, fb / fc);
+                    (
+                        s * (2.0 * middle * q * (q - r) - (b - a) * (r - 1.0)),
+                        (q - 1.0) * (r - 1.0) * (s - 1.0),
+                    )
+                }
+            };
+            match p > 0.0 {
+                true => q = -q,
+                false => p = -p,
+            }
+            if 2.0 * p < (3.0 * middle * q - (tolerance * q).abs()).min((e * q).abs()) {
+                e = d;
+                d = p / q;
+            } else {
+                d = middle;
+                e = d;
+            }
+        } else {
+            d = middle;
+            e = d;
+        }
+        (a, fa) = (b, fb);
+        b += match d.abs() > tolerance {
+            true => d,
+            false => tolerance.copysign(middle),
+        };
+        fb = f(b);
+        if !fb.is_finite() {
+            return Err(RootError::NotFinite { x: b });
+        }
+    }
+    Err(RootError::TooManyIterations {
+        iterations: iterations + MAX_ITERATIONS,
+        residual: fb.abs(),
+    })
+}
+
+/// Range of steps from zero, where the slope is negative, to where it turns positive. Steps
+/// stop at `limit`, which is returned alone when the slope is still negative there, and zero
+/// is returned alone when the slope isn't negative to begin with.
+pub(crate) fn bracket(slope: &dyn Fn(f64) -> f64, limit: f64) -> Result<(f64, f64), RootError> {
+    if slope(0.0) >= 0.0 {
+        return Ok((0.0, 0.0));
+    }
+    // Largest step known to still go downhill
+    let mut inner = 0.0;
+    let mut step = limit.min(0.5);
+    for _ in 0..100 {
+        let value = slope(step);
+        if !value.is_finite() {
+            // Pulls back toward where the model is defined
+            step = (inner + step) / 2.0;
+            continue;
+        }
+        if value >= 0.0 {
+            return Ok((inner, step));
+        }
+        if step >= limit {
+            return Ok((limit, limit));
+        }
+        inner = step;
+        step = (step * 2.0).min(limit);
+    }
+    Err(RootError::NoBracket {
+        low: inner,
+        high: step,
+    })
+}
+
+/// Solution of a small linear system by Gaussian elimination, none when it is singular
+fn solve(mut matrix: Vec<Vec<f64>>, mut vector: Vec<f64>) -> Option<Vec<f64>> {
+    let size = vector.len();
+    for column in 0..size {
+        let pivot = (column..size).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() < 1e-300 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        vector.swap(column, pivot);
+        let pivot_row = matrix[column].clone();
+        for row in column + 1..size {
+            let factor = matrix[row][column] / pivot_row[column];
+            for (value, pivot) in matrix[row].iter_mut().zip(&pivot_row).skip(column) {
+                *value -= factor * pivot;
+            }
+            vector[row] -= factor * vector[column];
+        }
+    }
+    let mut solution = vec![0.0; size];
+    for row in (0..size).rev() {
+        let sum: f64 = (row + 1..size).map(|x| matrix[row][x] * solution[x]).sum();
+        solution[row] = (vector[row] - sum) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+/// Second derivatives of `objective` at `point` by central differences
+fn get_hessian(objective: &dyn Fn(&[f64]) -> f64, point: &[f64]) -> Vec<Vec<f64>> {
+    let size = point.len();
+    let at = |row: usize, row_step: f64, column: usize, column_step: f64| {
+        let mut moved = point.to_vec();
+        moved[row] += row_step;
+        moved[column] += column_step;
+        objective(&moved)
+    };
+    (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|column| {
+                    (at(row, STEP, column, STEP)
+                        - at(row, STEP, column, -STEP)
+                        - at(row, -STEP, column, STEP)
+                        + at(row, -STEP, column, -STEP))
+                        / (4.0 * STEP * STEP)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Estimates every parameter of a model from the failures counted by each time, starting
+/// from `initial`. Each round steps in the Newton direction of the objective, using numeric
+/// derivatives of the logarithm of the parameters to keep them positive, and finds how far
+/// to go by solving for where the slope along that direction is zero.
+pub(crate) fn fit(
+    method: FitMethod,
+    mean_value: MeanValue,
+    times: &[f64],
+    counts: &[f64],
+    initial: &[f64],
+) -> Result<Fit, String> {
+    let objective = |log_params: &[f64]| {
+        let params: Vec<f64> = log_params.iter().map(|x| x.exp()).collect();
+        get_objective(method, mean_value, &params, times, counts)
+    };
+    let moved = |log_params: &[f64], direction: &[f64], step: f64| -> Vec<f64> {
+        log_params
+            .iter()
+            .zip(direction)
+            .map(|(x, d)| x + d * step)
+            .collect()
+    };
+    let size = initial.len();
+    let unit =
+        |index: usize| -> Vec<f64> { (0..size).map(|x| (x == index) as u8 as f64).collect() };
+    let mut log_params: Vec<f64> = initial.iter().map(|x| x.ln()).collect();
+    let mut iterations = 0;
+    for round in 1..=MAX_ROUNDS {
+        let before = objective(&log_params);
+        if !before.is_finite() {
+            return Err("the model can't produce the observed errors".to_string());
+        }
+        let at = |direction: &[f64], step: f64| objective(&moved(&log_params, direction, step));
+        let gradient: Vec<f64> = (0..size)
+            .map(|x| (at(&unit(x), STEP) - at(&unit(x), -STEP)) / (2.0 * STEP))
+            .collect();
+        let residual =
+            gradient.iter().fold(0.0, |a: f64, b| a.max(b.abs())) / before.abs().max(1.0);
+        // A flat objective has settled, such as when a parameter tends to zero
+        if residual <= TOLERANCE {
+            return Ok(Fit {
+                params: log_params.iter().map(|x| x.exp()).collect(),
+                rounds: round,
+                iterations,
+                residual,
+            });
+        }
+        let hessian = get_hessian(&objective, &log_params);
+        let downhill: Vec<f64> = gradient.iter().map(|x| -x).collect();
+        // Falls back to going straight downhill when the Newton step doesn't
+        let direction = match solve(hessian, downhill.clone()) {
+            Some(newton)
+                if newton
+                    .iter()
+                    .zip(&gradient)
+                    .map(|(a, b)| a * b)
+                    .sum::<f64>()
+                    < 0.0 =>
+            {
+                newton
+            }
+            _ => downhill,
+        };
+        // Keeps every parameter within a factor of e^40 of one
+        let limit = log_params
+            .iter()
+            .zip(&direction)
+            .map(|(x, d)| match *d > 0.0 {
+                true => (LOG_LIMIT - x) / d,
+                false => (-LOG_LIMIT - x) / d,
+            })
+            .fold(f64::INFINITY, f64::min)
+            .max(0.0);
+        let length = direction.iter().map(|x| x * x).sum::<f64>().sqrt();
+        let h = STEP / length;
+        let slope = |step: f64| (at(&direction, step + h) - at(&direction, step - h)) / (2.0 * h);
+        let curvature = |step: f64| {
+            (at(&direction, step + h) - 2.0 * at(&direction, step) + at(&direction, step - h))
+                / (h * h)
+        };
+        let step =
+            match bracket(&slope, limit).map_err(|error| format!("no step downhill, {error}"))? {
+                (low, high) if low == high => high,
+                (low, high) => {
+                    let root = find_root(&slope, &curvature, low, high)
+                        .map_err(|error| format!("unable to find the step size, {error}"))?;
+                    iterations += root.iterations;
+                    root.x
+                }
+            };
+        if at(&direction, step) < before {
+            log_params = moved(&log_params, &direction, step);
+        }
+        if before - objective(&log_params) <= TOLERANCE * before.abs().max(1.0) {
+            return Ok(Fit {
+                params: log_params.iter().map(|x| x.exp()).collect(),
+                rounds: round,
+                iterations,
+                residual,
+            });
+        }
+    }
+    Err(format!(
+        "the parameters didn't settle after {MAX_ROUNDS} rounds"
+    ))
+}
+
+/// How well the expected failures of a fit match the observed ones
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct GoodnessOfFit {
+    /// Sum of squared distances to the cumulative failures
+    pub(crate) sse: f64,
+    pub(crate) mse: f64,
+    pub(crate) r_squared: f64,
+    /// Kolmogorov-Smirnov distance between the observed and expected share of failures by each time
+    pub(crate) ks: f64,
+    pub(crate) log_likelihood: f64,
+    pub(crate) aic: f64,
+    pub(crate) bic: f64,
+}
+
+/// Goodness of fit of the expected failures by each time of a model with `param_count` parameters
+pub(crate) fn get_goodness_of_fit(
+    counts: &[f64],
+    mean_values: &[f64],
+    param_count: usize,
+) -> GoodnessOfFit {
+    let length = counts.len() as f64;
+    let total: f64 = counts.iter().sum();
+    let cumulative: Vec<f64> = counts
+        .iter()
+        .scan(0.0, |sum, x| {
+            *sum += x;
+            Some(*sum)
+        })
+        .collect();
+    let sse = get_sse(counts, mean_values);
+    let average = cumulative.iter().sum::<f64>() / length;
+    let sst: f64 = cumulative.iter().map(|x| f64::powi(x - average, 2)).sum();
+    let expected_total = mean_values.last().unwrap();
+    let ks = cumulative
+        .iter()
+        .zip(mean_values)
+        .map(|(observed, expected)| (observed / total - expected / expected_total).abs())
+        .fold(0.0, f64::max);
+    // Includes the factorials of the counts so the likelihood is that of the Poisson counts
+    let log_likelihood = get_log_likelihood(counts, mean_values) - get_log_factorials(counts);
+    let param_count = param_count as f64;
+    GoodnessOfFit {
+        sse,
+        mse: sse / length,
+        r_squared: 1.0 - sse / sst,
+        ks,
+        log_likelihood,
+        aic: 2.0 * param_count - 2.0 * log_likelihood,
+        bic: param_count * length.ln() - 2.0 * log_likelihood,
+    }
+}
+
+impl fmt::Display for GoodnessOfFit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SSE: {:.3}, MSE: {:.3}, R\u{b2}: {:.4}, KS: {:.4}, log-likelihood: {:.3}, AIC: {:.3}, BIC: {:.3}",
+            self.sse, self.mse, self.r_squared, self.ks, self.log_likelihood, self.aic, self.bic
+        )
+    }
+}
+
+/// Statistic models are ranked by when they are picked automatically
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Criterion {
+    Aic,
+    Bic,
+    Sse,
+    RSquared,
+    Ks,
+}
+
+impl Criterion {
+    /// Value of the statistic arranged so that smaller is better
+    pub(crate) fn get_score(self, goodness: &GoodnessOfFit) -> f64 {
+        match self {
+            Criterion::Aic => goodness.aic,
+            Criterion::Bic => goodness.bic,
+            Criterion::Sse => goodness.sse,
+            Criterion::RSquared => -goodness.r_squared,
+            Criterion::Ks => goodness.ks,
+        }
+    }
+}
+
+/// Covariance of the logarithm of the fitted parameters from the inverse of the observed
+/// information, or none when the information matrix can't be inverted. Least squares fits
+/// scale it by the variance left in the residuals.
+pub(crate) fn get_covariance(
+    method: FitMethod,
+    mean_value: MeanValue,
+    params: &[f64],
+    times: &[f64],
+    counts: &[f64],
+) -> Option<Vec<Vec<f64>>> {
+    let objective = |log_params: &[f64]| {
+        let params: Vec<f64> = log_params.iter().map(|x| x.exp()).collect();
+        get_objective(method, mean_value, &params, times, counts)
+    };
+    let log_params: Vec<f64> = params.iter().map(|x| x.ln()).collect();
+    let hessian = get_hessian(&objective, &log_params);
+    let size = params.len();
+    // Half the Hessian of the SSE over the residual variance is the information of a least
+    // squares fit
+    let factor = match method {
+        FitMethod::Mle => 1.0,
+        FitMethod::LeastSquares => {
+            let degrees = times.len().checked_sub(size).filter(|x| *x > 0)?;
+            2.0 * objective(&log_params) / degrees as f64
+        }
+    };
+    let columns: Vec<Vec<f64>> = (0..size)
+        .map(|column| {
+            let unit: Vec<f64> = (0..size).map(|x| (x == column) as u8 as f64).collect();
+            solve(hessian.clone(), unit)
+        })
+        .collect::<Option<_>>()?;
+    let covariance: Vec<Vec<f64>> = (0..size)
+        .map(|row| columns.iter().map(|x| x[row] * factor).collect())
+        .collect();
+    match covariance.iter().flatten().all(|x| x.is_finite()) {
+        true => Some(covariance),
+        false => None,
+    }
+}
+
+/// Value a standard normal variable falls below with probability `p`, using Acklam's
+/// rational approximation
+pub(crate) fn get_normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    let polynomial = |coefficients: &[f64], x: f64| coefficients.iter().fold(0.0, |a, b| a * x + b);
+    let tail = |p: f64| {
+        let q = (-2.0 * p.ln()).sqrt();
+        polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    };
+    match p {
+        p if p < 0.02425 => tail(p),
+        p if p > 1.0 - 0.02425 => -tail(1.0 - p),
+        p => {
+            let q = p - 0.5;
+            let r = q * q;
+            polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::models::MODELTYPES;
+
+    #[test]
+    fn newton_finds_a_root() {
+        let root = find_root(&|x| x * x - 2.0, &|x| 2.0 * x, 0.0, 2.0).unwrap();
+        assert!((root.x - 2f64.sqrt()).abs() < 1e-9);
+        assert!(root.iterations < 10);
+    }
+
+    #[test]
+    fn brent_takes_over_when_newton_leaves_the_bracket() {
+        // Newton's step from the middle of the range lands far outside of it
+        let root = find_root(&|x: f64| x.atan(), &|x| 1.0 / (1.0 + x * x), -10.0, 20.0).unwrap();
+        assert!(root.x.abs() < 1e-8);
+        // A derivative of zero leaves Newton no step at all
+        let root = find_root(&|x| x - 1.0, &|_| 0.0, 0.0, 3.0).unwrap();
+        assert!((root.x - 1.0).abs() < 1e-8);
+    }
+
+    #[test]
+    fn no_sign_change_is_no_bracket() {
+        let error = find_root(&|x| x * x + 1.0, &|x| 2.0 * x, -1.0, 1.0).unwrap_err();
+        assert_eq!(
+            error,
+            RootError::NoBracket {
+                low: -1.0,
+                high: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn undefined_value_is_not_finite() {
+        let error = find_root(&|x: f64| x.ln(), &|x| 1.0 / x, -1.0, 2.0).unwrap_err();
+        assert_eq!(error, RootError::NotFinite { x: -1.0 });
+    }
+
+    #[test]
+    fn bracket_finds_where_the_slope_turns() {
+        let (low, high) = bracket(&|x| x - 3.0, 100.0).unwrap();
+        assert!(low < 3.0 && 3.0 <= high);
+        assert_eq!(bracket(&|x| x + 1.0, 100.0), Ok((0.0, 0.0)));
+        assert_eq!(bracket(&|x| x - 200.0, 100.0), Ok((100.0, 100.0)));
+    }
+
+    #[test]
+    fn goel_okumoto_fit_recovers_its_parameters() {
+        let (a, b) = (120.0, 0.08);
+        let mean_value = |params: &[f64], t: f64| MODELTYPES::GO.get_mean_value(params, t);
+        let times: Vec<f64> = (1..=40).map(|x| x as f64).collect();
+        let counts: Vec<f64> = times
+            .iter()
+            .map(|t| mean_value(&[a, b], *t) - mean_value(&[a, b], t - 1.0))
+            .collect();
+        for method in [FitMethod::Mle, FitMethod::LeastSquares] {
+            let fit = fit(method, &mean_value, &times, &counts, &[60.0, 0.02]).unwrap();
+            assert!(
+                (fit.params[0] - a).abs() / a < 1e-3,
+                "{method:?} {:?}",
+                fit.params
+            );
+            assert!(
+                (fit.params[1] - b).abs() / b < 1e-3,
+                "{method:?} {:?}",
+                fit.params
+            );
+        }
+    }
+}
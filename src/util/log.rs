@@ -1,5 +1,7 @@
 use std::{collections::HashMap, fmt, str};
 
+use super::log_analyzer::Dimension;
+
 // use chrono::{TimeZone, Utc};
 
 #[derive(Clone, Debug)]
@@ -11,6 +13,10 @@ pub(crate) struct Log {
     pub(crate) request: String,
     pub(crate) status_code: (u16, String),
     pub(crate) size: f64,
+    pub(crate) referer: String,
+    pub(crate) user_agent: String,
+    /// Name of the file the log was read from, such as the host that wrote it
+    pub(crate) source: String,
 }
 
 impl Log {
@@ -39,20 +45,82 @@ impl Log {
             .map(|x| x.trim_matches('['))
             .map(|x| {
                 x.parse()
-                    .expect(&format!("Unable to parse into u32: {}", x))
+                    .unwrap_or_else(|_| panic!("Unable to parse into u32: {}", x))
             })
             .collect();
         let (year, day) = (date[2], date[0]);
         date[2] = day;
         date[0] = year;
-        return date;
+        date
+    }
+
+    /// Path of the requested endpoint without the query string
+    pub(crate) fn get_path(&self) -> String {
+        let request = self.request.trim_matches('"');
+        let target = request.split('%').nth(1).unwrap_or(request);
+        target.split('?').next().unwrap_or(target).to_string()
+    }
+
+    /// Value of a dimension used as the key of a group
+    pub(crate) fn get_dimension(&self, dimension: Dimension) -> String {
+        match dimension {
+            Dimension::Ip => self.ip.clone(),
+            Dimension::Subnet => self.get_subnet(),
+            Dimension::User => self.user_id.clone(),
+            Dimension::Status => format!("{} {}", self.status_code.0, self.status_code.1),
+            Dimension::Method => self.get_method(),
+            Dimension::Path => self.get_path(),
+            Dimension::Agent => self.get_agent_family(),
+            Dimension::UserAgent => self.user_agent.clone(),
+            Dimension::Referer => self.referer.clone(),
+            Dimension::Source => self.source.clone(),
+        }
+    }
+
+    /// Method of the request such as GET or POST
+    pub(crate) fn get_method(&self) -> String {
+        let request = self.request.trim_matches('"');
+        request.split('%').next().unwrap_or(request).to_string()
+    }
+
+    /// The /24 network of an IPv4 address, other addresses are kept whole
+    pub(crate) fn get_subnet(&self) -> String {
+        let parts: Vec<&str> = self.ip.split('.').collect();
+        match parts.len() {
+            4 => format!("{}.{}.{}.0/24", parts[0], parts[1], parts[2]),
+            _ => self.ip.clone(),
+        }
+    }
+
+    /// Browser or client family of the user agent
+    pub(crate) fn get_agent_family(&self) -> String {
+        let agent = self.user_agent.to_lowercase();
+        let families = [
+            ("bot", "Bot"),
+            ("crawler", "Bot"),
+            ("spider", "Bot"),
+            ("curl/", "curl"),
+            ("wget/", "Wget"),
+            ("python", "Python"),
+            ("edg/", "Edge"),
+            ("opr/", "Opera"),
+            ("firefox/", "Firefox"),
+            ("chrome/", "Chrome"),
+            ("safari/", "Safari"),
+        ];
+        // Browsers name the engines they are compatible with so the order matters
+        match families.iter().find(|(pattern, _)| agent.contains(pattern)) {
+            Some((_, family)) => family.to_string(),
+            None if agent.is_empty() || agent == "-" => "Unknown".to_string(),
+            None => "Other".to_string(),
+        }
     }
 
     pub(super) fn get_values_string(self) -> String {
-        return format!(
+        format!(
             "{},{},{},{},{}",
             self.ip, self.time, self.client_id, self.user_id, self.status_code.0,
-        );
+        )
     }
     /* Deprecated
     pub(super) fn utc_time(self) -> i64 {
@@ -68,8 +136,8 @@ impl Log {
 
 impl PartialEq for Log {
     fn eq(&self, other: &Self) -> bool {
-        <Log as Clone>::clone(&self).get_values_string()
-            == <Log as Clone>::clone(&other).get_values_string()
+        <Log as Clone>::clone(self).get_values_string()
+            == <Log as Clone>::clone(other).get_values_string()
     }
 }
 
@@ -77,8 +145,8 @@ impl fmt::Display for Log {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "ip: {}\nclient_id: {}\nuser_id: {}\ntime: {}\nrequest: {}\nstatus_code: {:?}\nsize: {}\n",
-            self.ip, self.client_id, self.user_id,self.time,self.request,self.status_code,self.size
+            "ip: {}\nclient_id: {}\nuser_id: {}\ntime: {}\nrequest: {}\nstatus_code: {:?}\nsize: {}\nreferer: {}\nuser_agent: {}\nsource: {}\n",
+            self.ip, self.client_id, self.user_id,self.time,self.request,self.status_code,self.size,self.referer,self.user_agent,self.source
         )
     }
 }
@@ -1,8 +1,15 @@
 use std::collections::HashMap;
 
+use chrono::{DateTime, Datelike, Duration, Months, TimeZone, Timelike, Utc};
+use clap::ValueEnum;
 use indexmap::IndexMap;
 
-use super::{log_data::LogData, *};
+use super::{
+    log_data::LogData,
+    top::{SpaceSaving, TopBy},
+    unique::UniqueCounter,
+    *,
+};
 
 #[derive(Clone)]
 pub(crate) struct LogAnalyzer {
@@ -10,19 +17,85 @@ pub(crate) struct LogAnalyzer {
     pub(crate) time_multi: i64,
 }
 
+/// Parts of a log that requests can be counted or grouped by
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Dimension {
+    Ip,
+    /// The /24 network of the IP address
+    Subnet,
+    User,
+    Status,
+    Method,
+    #[value(alias = "endpoint")]
+    Path,
+    /// Browser or client family of the user agent
+    Agent,
+    /// Whole user agent string
+    UserAgent,
+    Referer,
+    /// File the log was read from
+    Source,
+}
+
+/// Time ranges logs can be bucketed by along with their length in seconds
+pub(crate) const TIME_RANGES: [(&str, i64); 6] = [
+    ("sec", 1),
+    ("min", 60),
+    ("hour", 3600),
+    ("day", 86400),
+    ("month", 2629800),
+    ("year", 31556952),
+];
+
+/// Length of a time range in seconds, months and years are averaged
+pub(crate) fn get_time_multiplier(range: &str) -> Option<i64> {
+    TIME_RANGES
+        .iter()
+        .find(|(name, _)| *name == range)
+        .map(|(_, seconds)| *seconds)
+}
+
+/// Number of date parts (year, month, day, hour, min, sec) kept for a time range
+fn get_range_index(range: &str) -> usize {
+    let range_to_index: HashMap<&str, usize> = HashMap::from([
+        ("sec", 6),
+        ("min", 5),
+        ("hour", 4),
+        ("day", 3),
+        ("month", 2),
+        ("year", 1),
+    ]);
+    range_to_index[range]
+}
+
 impl LogAnalyzer {
+    /// Start and end of the time bucket that a parsed date falls into
+    fn get_bucket_bounds(&self, date: &[u32], range: &str) -> (DateTime<Utc>, DateTime<Utc>) {
+        let index = get_range_index(range);
+        // Parts outside of the range are reset to the start of the bucket
+        let part = |i: usize, default: u32| if i < index { date[i] } else { default };
+        let start = Utc
+            .with_ymd_and_hms(
+                date[0] as i32,
+                part(1, 1),
+                part(2, 1),
+                part(3, 0),
+                part(4, 0),
+                part(5, 0),
+            )
+            .unwrap();
+        let end = match range {
+            "year" => start + Months::new(12),
+            "month" => start + Months::new(1),
+            _ => start + Duration::seconds(self.time_multi),
+        };
+        (start, end)
+    }
+
     /// Group the logs by a specified time and store the stats in as well
     fn group_logs_by(self, range: &str) -> IndexMap<String, Vec<Log>> {
         let mut log_by_time: IndexMap<String, Vec<Log>> = IndexMap::new();
-        let range_to_index: HashMap<&str, usize> = HashMap::from([
-            ("sec", 6),
-            ("min", 5),
-            ("hour", 4),
-            ("day", 3),
-            ("month", 2),
-            ("year", 1),
-        ]);
-        let range = range_to_index[range];
+        let range = get_range_index(range);
         for log in self.logs {
             let mut time_group = String::new();
             let mut time = log.clone().get_parsed_date();
@@ -41,10 +114,11 @@ impl LogAnalyzer {
             }
         }
 
-        return log_by_time;
+        log_by_time
     }
 
-    fn get_total_data(self) -> LogData {
+    /// Totals of every log, taking the users merged from the buckets
+    fn get_total_data(self, users: &UniqueCounter) -> LogData {
         let start_time = self
             .logs
             .clone()
@@ -59,18 +133,21 @@ impl LogAnalyzer {
             .map(|x| to_time(&x.get_parsed_date()) as i64)
             .max()
             .unwrap();
-        let time =
-            time_difference(start_time as f64, end_time as f64) as f64 / self.time_multi as f64;
+        let time = time_difference(start_time as f64, end_time as f64) / self.time_multi as f64;
+        let start_time = Utc.timestamp_opt(start_time, 0).unwrap();
+        let end_time = Utc.timestamp_opt(end_time, 0).unwrap();
         let log_count = self.logs.len();
-        let (users, count) = get_users(self.logs.to_vec());
-        let sessions = get_sessions(7200.0, users);
+        let sessions = get_sessions(7200.0, &self.logs);
         let (errors, error_logs) = count_errors(self.logs.to_vec());
         let (total_bytes, avg_bytes) = get_byte_info(self.logs.to_vec());
         let atbl = get_avg_time(self.logs.to_vec());
         let atbe = get_avg_time(error_logs);
-        let data = LogData {
+        let status_classes = count_status_classes(&self.logs);
+        LogData {
+            start_time,
+            end_time,
             time,
-            users: count,
+            users: users.count(),
             sessions,
             total_bytes,
             avg_bytes,
@@ -78,8 +155,101 @@ impl LogAnalyzer {
             log_count,
             atbl,
             atbe,
-        };
-        return data;
+            status_classes,
+        }
+    }
+
+    /// Splits the logs by the values of one or more dimensions, largest group first
+    fn group_logs_by_dimensions(&self, dimensions: &[Dimension]) -> IndexMap<String, Vec<Log>> {
+        let mut log_by_group: IndexMap<String, Vec<Log>> = IndexMap::new();
+        for log in &self.logs {
+            let group = dimensions
+                .iter()
+                .map(|x| log.get_dimension(*x))
+                .collect::<Vec<String>>()
+                .join(" ");
+            log_by_group.entry(group).or_default().push(log.clone());
+        }
+        log_by_group.sort_by(|_, a, _, b| b.len().cmp(&a.len()));
+        log_by_group
+    }
+
+    /// Buckets and totals of every group of logs sharing the same dimension values
+    pub(crate) fn get_group_data(
+        &self,
+        dimensions: &[Dimension],
+        time: &str,
+    ) -> Vec<(String, Vec<LogData>, LogData)> {
+        let mut groups = Vec::new();
+        for (group, logs) in self.group_logs_by_dimensions(dimensions) {
+            let analyzer = LogAnalyzer {
+                logs,
+                time_multi: self.time_multi,
+            };
+            let (mut data, total) = analyzer.get_data(time);
+            data.sort_by_key(|x| x.start_time);
+            groups.push((group, data, total));
+        }
+        groups
+    }
+
+    /// Counts the requests for each value of a field, keeping the `count` most common
+    pub(crate) fn get_top_counts(&self, field: Dimension, count: usize) -> Vec<(String, f64)> {
+        let mut counts: IndexMap<String, f64> = IndexMap::new();
+        for log in &self.logs {
+            *counts.entry(log.get_dimension(field)).or_insert(0.0) += 1.0;
+        }
+        counts.sort_by(|_, a, _, b| b.total_cmp(a));
+        counts.into_iter().take(count).collect()
+    }
+
+    /// Ranks the values of a dimension, keeping at most `capacity` counters in memory
+    pub(crate) fn get_heavy_hitters(
+        &self,
+        field: Dimension,
+        by: TopBy,
+        capacity: usize,
+    ) -> SpaceSaving {
+        let mut counts = SpaceSaving::new(capacity);
+        for log in &self.logs {
+            counts.add(log.get_dimension(field), by.get_weight(log));
+        }
+        counts
+    }
+
+    /// Logs from `since` up to but not including `until`
+    pub(crate) fn get_window(
+        &self,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> LogAnalyzer {
+        let logs = self
+            .logs
+            .iter()
+            .filter(|log| {
+                let time = Utc
+                    .timestamp_opt(to_time(&(*log).clone().get_parsed_date()) as i64, 0)
+                    .unwrap();
+                since.is_none_or(|x| time >= x) && until.is_none_or(|x| time < x)
+            })
+            .cloned()
+            .collect();
+        LogAnalyzer {
+            logs,
+            time_multi: self.time_multi,
+        }
+    }
+
+    /// Counts the requests for every hour of every day of the week, starting on Monday
+    pub(crate) fn get_weekly_traffic(&self) -> [[f64; 24]; 7] {
+        let mut traffic = [[0.0; 24]; 7];
+        for log in &self.logs {
+            let time = Utc
+                .timestamp_opt(to_time(&log.clone().get_parsed_date()) as i64, 0)
+                .unwrap();
+            traffic[time.weekday().num_days_from_monday() as usize][time.hour() as usize] += 1.0;
+        }
+        traffic
     }
 
     pub(crate) fn get_data(self, time: &str) -> (Vec<LogData>, LogData) {
@@ -92,12 +262,16 @@ impl LogAnalyzer {
             .unwrap();
         let grouped_logs = self.clone().group_logs_by(time);
         let mut data: Vec<LogData> = Vec::new();
+        let mut all_users = UniqueCounter::default();
         for group in grouped_logs.values() {
             let log_count = group.len();
+            let (bucket_start, bucket_end) =
+                self.get_bucket_bounds(&group[0].clone().get_parsed_date(), time);
             let time = time_difference(start_time as f64, get_avg_group_time(group.to_vec()))
                 / self.time_multi as f64;
-            let (users, count) = get_users(group.to_vec());
-            let sessions = get_sessions(7200.0, users);
+            let users = count_users(group);
+            all_users.merge(&users);
+            let sessions = get_sessions(7200.0, group);
             let (errors, error_logs) = count_errors(group.to_vec());
             let (total_bytes, avg_bytes) = get_byte_info(group.to_vec());
             let atbl = get_avg_time(group.to_vec());
@@ -105,10 +279,13 @@ impl LogAnalyzer {
                 0 => 0.0,
                 _ => get_avg_time(error_logs),
             };
+            let status_classes = count_status_classes(group);
 
             data.push(LogData {
+                start_time: bucket_start,
+                end_time: bucket_end,
                 time,
-                users: count,
+                users: users.count(),
                 sessions,
                 total_bytes,
                 avg_bytes,
@@ -116,10 +293,11 @@ impl LogAnalyzer {
                 log_count,
                 atbl,
                 atbe,
+                status_classes,
             })
         }
-        let total_data = self.get_total_data();
+        let total_data = self.get_total_data(&all_users);
         /* `LogData` value */
-        return (data, total_data);
+        (data, total_data)
     }
 }
@@ -1,7 +1,79 @@
 use std::fmt;
 
-#[derive(Clone)]
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Values of a `LogData` that can be plotted
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+#[value(rename_all = "snake_case")]
+pub(crate) enum Metric {
+    /// Time between the first log and the average time of the logs in the bucket
+    Time,
+    /// Unique IP addresses
+    Users,
+    /// Visits from one IP with no gap longer than two hours
+    Sessions,
+    /// Size of every response added together
+    TotalBytes,
+    /// Average size of a response
+    AvgBytes,
+    /// Number of requests
+    Hits,
+    /// Responses with a 4xx or 5xx status code
+    Errors,
+    /// Average time between logs
+    Atbl,
+    /// Average time between errors
+    Atbe,
+    /// Responses with a 2xx status code
+    #[value(name = "2xx")]
+    Status2xx,
+    /// Responses with a 3xx status code
+    #[value(name = "3xx")]
+    Status3xx,
+    /// Responses with a 4xx status code
+    #[value(name = "4xx")]
+    Status4xx,
+    /// Responses with a 5xx status code
+    #[value(name = "5xx")]
+    Status5xx,
+}
+
+impl Metric {
+    pub(crate) fn get_unit(self) -> &'static str {
+        match self {
+            Metric::Time => "buckets",
+            Metric::Users
+            | Metric::Sessions
+            | Metric::Hits
+            | Metric::Errors
+            | Metric::Status2xx
+            | Metric::Status3xx
+            | Metric::Status4xx
+            | Metric::Status5xx => "count",
+            Metric::TotalBytes | Metric::AvgBytes => "kB",
+            Metric::Atbl | Metric::Atbe => "seconds",
+        }
+    }
+
+    pub(crate) fn get_description(self) -> String {
+        self.to_possible_value()
+            .and_then(|x| x.get_help().map(|help| help.to_string()))
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+#[derive(Clone, Serialize)]
 pub(crate) struct LogData {
+    pub(crate) start_time: DateTime<Utc>,
+    pub(crate) end_time: DateTime<Utc>,
     pub(crate) time: f64,
     pub(crate) users: usize,
     pub(crate) sessions: u64,
@@ -11,48 +83,31 @@ pub(crate) struct LogData {
     pub(crate) errors: u64,
     pub(crate) atbl: f64,
     pub(crate) atbe: f64,
+    /// Responses by status class, from 2xx to 5xx
+    pub(crate) status_classes: [u64; 4],
 }
 impl LogData {
-    pub(crate) fn get_data(self, data_point: &str) -> f64 {
-        match data_point {
-            "time" => self.time,
-            "users" => self.users as f64,
-            "sessions" => self.sessions as f64,
-            "total_bytes" => self.total_bytes,
-            "avg_bytes" => self.avg_bytes,
-            "hits" => self.log_count as f64,
-            "errors" => self.errors as f64,
-            "atbl" => self.atbl,
-            "atbe" => self.atbe,
-            _ => self.errors as f64,
-        }
+    /// Start of the bucket as a unix timestamp for plotting against real time
+    pub(crate) fn get_timestamp(&self) -> f64 {
+        self.start_time.timestamp() as f64
     }
-    pub(crate) fn get_data_point(self, x_value: &str, y_value: &str) -> (f64, f64) {
-        let x = match x_value {
-            "time" => self.time,
-            "users" => self.users as f64,
-            "sessions" => self.sessions as f64,
-            "total_bytes" => self.total_bytes,
-            "avg_bytes" => self.avg_bytes,
-            "hits" => self.log_count as f64,
-            "errors" => self.errors as f64,
-            "atbl" => self.atbl,
-            "atbe" => self.atbe,
-            _ => self.errors as f64,
-        };
-        let y = match y_value {
-            "time" => self.time,
-            "users" => self.users as f64,
-            "sessions" => self.sessions as f64,
-            "total_bytes" => self.total_bytes,
-            "avg_bytes" => self.avg_bytes,
-            "hits" => self.log_count as f64,
-            "errors" => self.errors as f64,
-            "atbl" => self.atbl,
-            "atbe" => self.atbe,
-            _ => self.errors as f64,
-        };
-        return (x, y);
+
+    pub(crate) fn get_data(self, metric: Metric) -> f64 {
+        match metric {
+            Metric::Time => self.time,
+            Metric::Users => self.users as f64,
+            Metric::Sessions => self.sessions as f64,
+            Metric::TotalBytes => self.total_bytes,
+            Metric::AvgBytes => self.avg_bytes,
+            Metric::Hits => self.log_count as f64,
+            Metric::Errors => self.errors as f64,
+            Metric::Atbl => self.atbl,
+            Metric::Atbe => self.atbe,
+            Metric::Status2xx => self.status_classes[0] as f64,
+            Metric::Status3xx => self.status_classes[1] as f64,
+            Metric::Status4xx => self.status_classes[2] as f64,
+            Metric::Status5xx => self.status_classes[3] as f64,
+        }
     }
 }
 
@@ -1,135 +1,564 @@
-use std::f64::consts::E;
+use std::{f64::consts::E, fmt};
 
-use crate::LogData;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
 
-#[derive(Clone)]
+use super::fit::{self, Fit, FitMethod, GoodnessOfFit, RootError};
+use crate::{LogData, Metric};
+
+/// Points of a curve as `(x, y)` pairs
+pub(crate) type Curve = Vec<(f64, f64)>;
+/// Lower and upper bound of a curve at each x as `(x, low, high)`
+pub(crate) type Band = Vec<(f64, f64, f64)>;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum MODELTYPES {
     SCWIND,
     GO,
+    /// Yamada delayed S-shaped
+    YAMADA,
+    /// Ohba inflection S-shaped
+    INFLECTION,
+    /// Musa-Okumoto logarithmic Poisson
+    MUSA,
+    /// Crow-AMSAA or Duane power law
+    CROW,
+    WEIBULL,
+}
+
+/// Every model that can be fit
+pub(crate) const MODELS: [MODELTYPES; 7] = [
+    MODELTYPES::SCWIND,
+    MODELTYPES::GO,
+    MODELTYPES::YAMADA,
+    MODELTYPES::INFLECTION,
+    MODELTYPES::MUSA,
+    MODELTYPES::CROW,
+    MODELTYPES::WEIBULL,
+];
+
+impl MODELTYPES {
+    /// Meaning of each fitted parameter, in the order they are stored
+    fn get_parameter_names(self) -> Vec<&'static str> {
+        match self {
+            MODELTYPES::SCWIND => vec!["initial failure rate", "failure rate decay"],
+            MODELTYPES::GO | MODELTYPES::YAMADA => {
+                vec!["expected total failures", "failure detection rate"]
+            }
+            MODELTYPES::INFLECTION => vec![
+                "expected total failures",
+                "failure detection rate",
+                "inflection factor",
+            ],
+            MODELTYPES::MUSA => vec!["initial failure intensity", "intensity decay per failure"],
+            MODELTYPES::CROW => vec!["scale", "growth shape"],
+            MODELTYPES::WEIBULL => vec!["expected total failures", "scale", "shape"],
+        }
+    }
+
+    /// Expected number of failures by time `t`
+    pub(crate) fn get_mean_value(self, params: &[f64], t: f64) -> f64 {
+        match self {
+            MODELTYPES::SCWIND => params[0] / params[1] * (1.0 - f64::powf(E, -params[1] * t)),
+            MODELTYPES::GO => params[0] * (1.0 - f64::powf(E, -params[1] * t)),
+            MODELTYPES::YAMADA => {
+                params[0] * (1.0 - (1.0 + params[1] * t) * f64::powf(E, -params[1] * t))
+            }
+            MODELTYPES::INFLECTION => {
+                let decay = f64::powf(E, -params[1] * t);
+                params[0] * (1.0 - decay) / (1.0 + params[2] * decay)
+            }
+            MODELTYPES::MUSA => (params[0] * params[1] * t).ln_1p() / params[1],
+            MODELTYPES::CROW => params[0] * f64::powf(t, params[1]),
+            MODELTYPES::WEIBULL => {
+                params[0] * (1.0 - f64::powf(E, -params[1] * f64::powf(t, params[2])))
+            }
+        }
+    }
+
+    /// Expected failures per unit of time at time `t`, the derivative of the mean value
+    pub(crate) fn get_intensity(self, params: &[f64], t: f64) -> f64 {
+        match self {
+            MODELTYPES::SCWIND => params[0] * f64::powf(E, -params[1] * t),
+            MODELTYPES::GO => params[0] * params[1] * f64::powf(E, -params[1] * t),
+            MODELTYPES::YAMADA => {
+                params[0] * params[1] * params[1] * t * f64::powf(E, -params[1] * t)
+            }
+            MODELTYPES::INFLECTION => {
+                let decay = f64::powf(E, -params[1] * t);
+                params[0] * params[1] * (1.0 + params[2]) * decay
+                    / f64::powi(1.0 + params[2] * decay, 2)
+            }
+            MODELTYPES::MUSA => params[0] / (1.0 + params[0] * params[1] * t),
+            MODELTYPES::CROW => params[0] * params[1] * f64::powf(t, params[1] - 1.0),
+            MODELTYPES::WEIBULL => {
+                let power = f64::powf(t, params[2]);
+                params[0] * params[1] * params[2] * power / t * f64::powf(E, -params[1] * power)
+            }
+        }
+    }
+
+    /// Range searched for each shape parameter, every model being a scale times a shape curve
+    fn get_shape_bounds(self) -> Vec<(f64, f64)> {
+        match self {
+            MODELTYPES::SCWIND | MODELTYPES::GO | MODELTYPES::YAMADA => vec![(1e-8, 10.0)],
+            MODELTYPES::INFLECTION => vec![(1e-8, 10.0), (1e-3, 1e3)],
+            MODELTYPES::MUSA => vec![(1e-8, 1e3)],
+            MODELTYPES::CROW => vec![(0.05, 5.0)],
+            MODELTYPES::WEIBULL => vec![(1e-8, 10.0), (0.1, 5.0)],
+        }
+    }
+
+    /// Parameters of the model from the scale that multiplies the shape curve and the shape
+    fn get_params(self, scale: f64, shape: &[f64]) -> Vec<f64> {
+        match self {
+            MODELTYPES::MUSA => vec![scale * shape[0], 1.0 / scale],
+            _ => [&[scale], shape].concat(),
+        }
+    }
+
+    /// Mean value function of the model with a scale of one
+    fn get_shape_curve(self, shape: &[f64], t: f64) -> f64 {
+        match self {
+            MODELTYPES::MUSA => (shape[0] * t).ln_1p(),
+            _ => self.get_mean_value(&self.get_params(1.0, shape), t),
+        }
+    }
+}
+
+impl fmt::Display for MODELTYPES {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MODELTYPES::SCWIND => write!(f, "Schneidewind"),
+            MODELTYPES::GO => write!(f, "Goel-Okumoto"),
+            MODELTYPES::YAMADA => write!(f, "Yamada delayed S-shaped"),
+            MODELTYPES::INFLECTION => write!(f, "Inflection S-shaped"),
+            MODELTYPES::MUSA => write!(f, "Musa-Okumoto"),
+            MODELTYPES::CROW => write!(f, "Crow-AMSAA"),
+            MODELTYPES::WEIBULL => write!(f, "Weibull"),
+        }
+    }
+}
+
+/// Parameter rounded to four decimals, or to four significant digits when it is smaller
+fn format_parameter(value: f64) -> String {
+    match value != 0.0 && value.abs() < 1e-3 {
+        true => format!("{:.4e}", value),
+        false => format!("{:.4}", value),
+    }
+}
+
+/// Reliability model fit to the errors, or none to leave the model out
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ModelChoice {
+    Schneidewind,
+    /// Goel-Okumoto
+    Go,
+    /// Yamada delayed S-shaped
+    Yamada,
+    /// Inflection S-shaped
+    Inflection,
+    /// Musa-Okumoto logarithmic Poisson
+    MusaOkumoto,
+    /// Crow-AMSAA power law, also known as the Duane model
+    #[value(alias = "duane")]
+    CrowAmsaa,
+    /// Weibull-type growth curve
+    Weibull,
+    /// Fits every model and keeps the one that ranks best
+    Auto,
+    None,
+}
+
+impl ModelChoice {
+    /// Models to fit, more than one when they are compared
+    pub(crate) fn get_models(self) -> Vec<MODELTYPES> {
+        match self {
+            ModelChoice::Schneidewind => vec![MODELTYPES::SCWIND],
+            ModelChoice::Go => vec![MODELTYPES::GO],
+            ModelChoice::Yamada => vec![MODELTYPES::YAMADA],
+            ModelChoice::Inflection => vec![MODELTYPES::INFLECTION],
+            ModelChoice::MusaOkumoto => vec![MODELTYPES::MUSA],
+            ModelChoice::CrowAmsaa => vec![MODELTYPES::CROW],
+            ModelChoice::Weibull => vec![MODELTYPES::WEIBULL],
+            ModelChoice::Auto => MODELS.to_vec(),
+            ModelChoice::None => Vec::new(),
+        }
+    }
+}
+
+/// Narrows `[low, high]` down to the point where `f` is largest, assuming a single peak
+fn golden_section(f: &dyn Fn(f64) -> f64, mut low: f64, mut high: f64) -> f64 {
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..100 {
+        let left = high - ratio * (high - low);
+        let right = low + ratio * (high - low);
+        match f(left) >= f(right) {
+            true => high = right,
+            false => low = left,
+        }
+    }
+    (low + high) / 2.0
 }
 
 #[derive(Clone)]
 pub(crate) struct Models {
     pub(crate) model: MODELTYPES,
+    pub(crate) fit: FitMethod,
     pub(crate) total_errors: f64,
     pub(crate) log_data: Vec<LogData>,
-    pub(crate) data_point: usize,
+    /// Start and end of each bucket in the exposure the model is fit against
+    pub(crate) intervals: Vec<(f64, f64)>,
+    /// Chance that the confidence intervals hold the true value, such as 0.95
+    pub(crate) confidence: f64,
 }
 
 impl Models {
-    pub(crate) fn get_curve(self) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) /* , Vec<(f64, f64)>)*/ {
+    /// Expected failures and their confidence band at the start of each bucket, where the
+    /// failures before it are drawn, along with the expected failures within each bucket, or why
+    /// the model couldn't be fit. Every point is at the exposure the bucket starts at, one point
+    /// per bucket, so callers line them up with the x-axis they draw.
+    pub(crate) fn get_curve(self) -> Result<(Curve, Curve, Band), String> /* , Curve)*/ {
         let mut data_point: Vec<(f64, f64)> = Vec::new();
-        let mut data_prime: Vec<(f64, f64)> = Vec::new();
+        let mut data_increment: Vec<(f64, f64)> = Vec::new();
+        let mut band: Band = Vec::new();
         //let mut data_rate: Vec<(f64, f64)> = Vec::new();
-        let rate = match self.model {
-            MODELTYPES::SCWIND => {
-                let mut temp = 0.0;
-                let mut count = 1.0;
-                for log in &self.log_data {
-                    let current_errors = log.errors as f64;
-                    temp += count * current_errors / self.total_errors;
-                    count += 1.0;
-                }
-                temp
-            }
-            MODELTYPES::GO => {
-                let mut temp = 0.0;
-                for log in &self.log_data {
-                    temp += log.errors as f64;
-                }
-                temp
-            }
-        };
-        let mut starting_value = 0.1;
-        let mut b = f64::NAN;
-        let t = self.data_point as f64;
-        while b.is_nan() {
-            b = self.clone().newton_raphson(starting_value, t, rate);
-            starting_value = f64::powi(starting_value, 10);
-        }
-        let a = match self.model {
-            MODELTYPES::SCWIND => {
-                b * self.total_errors / (1.0 - f64::powf(E, -b * self.data_point as f64))
-            }
-            MODELTYPES::GO => {
-                //self.data_point as f64 / (1.0 - f64::powf(E, -(b * self.total_errors)))
-                self.total_errors
+        let fitted = self.get_fitted_model()?;
+
+        for (start, end) in &self.intervals {
+            let y = fitted.get_mean_value(*start);
+            // Counted over the whole bucket so it doesn't depend on the unit the model is fit in
+            let increment = fitted.get_mean_value(*end) - y;
+
+            data_point.push((*start, y));
+            data_increment.push((*start, increment));
+            if let Some((low, high)) = fitted.get_bounds(*start) {
+                band.push((*start, low, high));
             }
-        };
-        println!("{},{}", a, b);
-
-        for i in 0..self.data_point {
-            let (y, y_prime) = match self.model {
-                MODELTYPES::SCWIND => (
-                    a / b * (1.0 - f64::powf(E, -b * (i + 1) as f64)),
-                    a * f64::powf(E, -b * (i + 1) as f64),
-                ),
-                MODELTYPES::GO => (
-                    a * (1.0 - f64::powf(E, -b * (i + 1) as f64)),
-                    a * b * f64::powf(E, -b * (i + 1) as f64),
-                ),
-            };
-            //let y2 = a * f64::powf(E, -b * log_data_by_time[i].time);
-
-            data_point.push((i as f64, y));
-            data_prime.push((i as f64, y_prime))
             //data_rate.push((log_data_by_time[i].time as f64, y2));
         }
-        return (data_point, data_prime); //, data_rate);
+        Ok((data_point, data_increment, band)) //, data_rate);
     }
 
-    fn model(self, b: f64, t: f64) -> f64 {
-        match self.model {
-            MODELTYPES::SCWIND => {
-                let one = 1.0 / (f64::powf(E, b) - 1.0);
-                let two = t / (f64::powf(E, b * t) - 1.0);
-                return one - two;
+    /// Fits the model and prints its parameters, how the fit settled and how well it matches
+    pub(crate) fn get_fitted_model(&self) -> Result<FittedModel, String> {
+        let fit = self.fit_params()?;
+        let goodness = self.get_goodness_of_fit(&fit.params);
+        let names: Vec<String> = self
+            .model
+            .get_parameter_names()
+            .iter()
+            .zip(&fit.params)
+            .zip(["a", "b", "c"])
+            .map(|((name, value), symbol)| {
+                format!("{name} {symbol} = {}", format_parameter(*value))
+            })
+            .collect();
+        println!(
+            "{} model ({}): {}",
+            self.model,
+            self.fit.to_possible_value().unwrap().get_name(),
+            names.join(", ")
+        );
+        println!(
+            "Settled after {} rounds and {} solver iterations with a relative slope of {:.1e}",
+            fit.rounds, fit.iterations, fit.residual
+        );
+        println!("{}", goodness);
+        let fitted = FittedModel {
+            model: self.model,
+            covariance: self.get_covariance(&fit.params),
+            params: fit.params,
+            z: fit::get_normal_quantile(0.5 + self.confidence / 2.0),
+        };
+        let level = (self.confidence * 1000.0).round() / 10.0;
+        match &fitted.covariance {
+            Some(covariance) => {
+                let intervals: Vec<String> = fitted
+                    .params
+                    .iter()
+                    .enumerate()
+                    .zip(["a", "b", "c"])
+                    .map(|((index, value), symbol)| {
+                        let spread = fitted.z * covariance[index][index].sqrt();
+                        // The errors say next to nothing about a parameter this uncertain
+                        match spread <= fit::LOG_LIMIT {
+                            true => format!(
+                                "{symbol} in [{}, {}]",
+                                format_parameter(value * f64::powf(E, -spread)),
+                                format_parameter(value * f64::powf(E, spread))
+                            ),
+                            false => format!("{symbol} unbounded"),
+                        }
+                    })
+                    .collect();
+                println!("{level}% confidence intervals: {}", intervals.join(", "));
             }
-            MODELTYPES::GO => {
-                let n = self.total_errors;
+            None => println!("No confidence intervals, the information matrix can't be inverted"),
+        }
+        Ok(fitted)
+    }
+
+    /// Covariance of the logarithm of the parameters, or none when it can't be estimated
+    fn get_covariance(&self, params: &[f64]) -> Option<Vec<Vec<f64>>> {
+        fit::get_covariance(
+            self.fit,
+            &|params, t| self.model.get_mean_value(params, t),
+            params,
+            &self.get_times(),
+            &self.get_counts(),
+        )
+    }
+
+    /// Estimates the parameters of the model, or why it couldn't be fit
+    pub(crate) fn fit_params(&self) -> Result<Fit, String> {
+        if self.total_errors == 0.0 {
+            return Err("there are no errors to fit".to_string());
+        }
+        fit::fit(
+            self.fit,
+            &|params, t| self.model.get_mean_value(params, t),
+            &self.get_times(),
+            &self.get_counts(),
+            &self.get_initial_params(),
+        )
+    }
 
-                let one = t / b;
-                let two = t * n;
-                let three = f64::powf(E, -b * n) / (1.0 - f64::powf(E, -b * n));
-                return one - (two * three);
+    /// How closely the model with these parameters follows the errors
+    pub(crate) fn get_goodness_of_fit(&self, params: &[f64]) -> GoodnessOfFit {
+        let mean_values: Vec<f64> = self
+            .get_times()
+            .iter()
+            .map(|t| self.model.get_mean_value(params, *t))
+            .collect();
+        fit::get_goodness_of_fit(&self.get_counts(), &mean_values, params.len())
+    }
+
+    pub(crate) fn get_counts(&self) -> Vec<f64> {
+        self.log_data.iter().map(|x| x.errors as f64).collect()
+    }
+
+    /// Time at the end of each bucket, which the failures in it are counted by
+    pub(crate) fn get_times(&self) -> Vec<f64> {
+        self.intervals.iter().map(|x| x.1).collect()
+    }
+
+    /// Rough maximum likelihood parameters the fit starts from. The scale has a closed form
+    /// once the shape is known so only the shape parameters are searched, one at a time.
+    fn get_initial_params(&self) -> Vec<f64> {
+        let times = self.get_times();
+        let counts = self.get_counts();
+        let profile = |shape: &[f64]| {
+            let curve: Vec<f64> = times
+                .iter()
+                .map(|t| self.model.get_shape_curve(shape, *t))
+                .collect();
+            let scale = self.total_errors / curve.last().unwrap();
+            let mean_values: Vec<f64> = curve.iter().map(|x| x * scale).collect();
+            (scale, fit::get_log_likelihood(&counts, &mean_values))
+        };
+        let bounds = self.model.get_shape_bounds();
+        // Searches the logarithm of each parameter since their sizes vary by orders of magnitude
+        let mut shape: Vec<f64> = bounds
+            .iter()
+            .map(|(low, high)| (low * high).sqrt())
+            .collect();
+        for _ in 0..20 {
+            for (index, (low, high)) in bounds.iter().enumerate() {
+                let search = |x: f64| {
+                    let mut shape = shape.clone();
+                    shape[index] = f64::powf(E, x);
+                    profile(&shape).1
+                };
+                shape[index] = f64::powf(E, golden_section(&search, low.ln(), high.ln()));
             }
+        }
+        let (scale, _) = profile(&shape);
+        self.model.get_params(scale, &shape)
+    }
+}
+
+/// Start and end of each bucket in units of `unit_seconds` since the first bucket began, so
+/// buckets left out for having no logs still take up time
+fn get_elapsed_times(log_data: &[LogData], unit_seconds: f64) -> Vec<(f64, f64)> {
+    let Some(first) = log_data.first() else {
+        return Vec::new();
+    };
+    let elapsed =
+        |time: DateTime<Utc>| (time - first.start_time).num_seconds() as f64 / unit_seconds;
+    log_data
+        .iter()
+        .map(|x| (elapsed(x.start_time), elapsed(x.end_time)))
+        .collect()
+}
+
+/// What the models measure time in, either the clock or how much the service was used
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Exposure {
+    /// Elapsed time
+    Time,
+    /// Requests served
+    Hits,
+    /// Sessions started
+    Sessions,
+    /// Kilobytes sent
+    Bytes,
+}
+
+impl Exposure {
+    /// Exposure measured by a metric, none for metrics that don't add up to usage over time
+    pub(crate) fn from_metric(metric: Metric) -> Option<Exposure> {
+        match metric {
+            Metric::Time => Some(Exposure::Time),
+            Metric::Hits => Some(Exposure::Hits),
+            Metric::Sessions => Some(Exposure::Sessions),
+            Metric::TotalBytes => Some(Exposure::Bytes),
+            _ => None,
+        }
+    }
+
+    /// Metric that each bucket adds to the exposure, none for time
+    fn get_metric(self) -> Option<Metric> {
+        match self {
+            Exposure::Time => None,
+            Exposure::Hits => Some(Metric::Hits),
+            Exposure::Sessions => Some(Metric::Sessions),
+            Exposure::Bytes => Some(Metric::TotalBytes),
+        }
+    }
+
+    /// Name of one unit of usage, none for time which is measured in a chosen unit
+    pub(crate) fn get_unit(self) -> Option<&'static str> {
+        match self {
+            Exposure::Time => None,
+            Exposure::Hits => Some("hit"),
+            Exposure::Sessions => Some("session"),
+            Exposure::Bytes => Some("kilobyte"),
+        }
+    }
+
+    /// Start and end of each bucket in this exposure, with time in units of `unit_seconds`
+    pub(crate) fn get_intervals(self, log_data: &[LogData], unit_seconds: f64) -> Vec<(f64, f64)> {
+        let Some(metric) = self.get_metric() else {
+            return get_elapsed_times(log_data, unit_seconds);
         };
+        log_data
+            .iter()
+            .scan(0.0, |total, data| {
+                let start = *total;
+                *total += data.clone().get_data(metric);
+                Some((start, *total))
+            })
+            .collect()
     }
+}
 
-    /// Derivative of different reliability model
-    fn model_prime(self, b: f64, t: f64) -> f64 {
-        match self.model {
-            MODELTYPES::SCWIND => {
-                let one = f64::powf(E, b) / f64::powi(f64::powf(E, b) - 1.0, 2);
-                let two =
-                    f64::powi(t, 2) * f64::powf(E, b * t) / f64::powi(f64::powf(E, b * t) - 1.0, 2);
-                return two - one;
-            }
-            MODELTYPES::GO => {
-                let t = self.data_point as f64;
-                let n = self.total_errors;
-                let one = -t / f64::powi(b, 2);
-                let two = t * f64::powi(n, 2);
-                let three = 1.0 / (1.0 - f64::powf(E, -b * n));
-                let four = f64::powf(E, -b * n) / (1.0 - f64::powf(E, (-b * n) * 2.0));
-                let five = f64::powf(E, b * n);
-                return one + ((two * (three + four)) * five);
-            }
+/// Furthest ahead, in units of exposure, that a target failure intensity is looked for
+const TARGET_LIMIT: f64 = 1e9;
+
+/// Model with its fitted parameters, which can be evaluated past the last bucket
+#[derive(Clone, Debug)]
+pub(crate) struct FittedModel {
+    pub(crate) model: MODELTYPES,
+    pub(crate) params: Vec<f64>,
+    /// Covariance of the logarithm of the parameters, none when it can't be estimated
+    pub(crate) covariance: Option<Vec<Vec<f64>>>,
+    /// Standard normal quantile of the confidence level
+    pub(crate) z: f64,
+}
+
+impl FittedModel {
+    pub(crate) fn get_mean_value(&self, t: f64) -> f64 {
+        self.model.get_mean_value(&self.params, t)
+    }
+
+    pub(crate) fn get_intensity(&self, t: f64) -> f64 {
+        self.model.get_intensity(&self.params, t)
+    }
+
+    /// Confidence bounds of the expected failures by time `t`, none without a covariance
+    pub(crate) fn get_bounds(&self, t: f64) -> Option<(f64, f64)> {
+        let covariance = self.covariance.as_ref()?;
+        let y = self.get_mean_value(t);
+        let variance =
+            self.get_variance(covariance, &|params| self.model.get_mean_value(params, t));
+        let spread = self.z * variance.sqrt();
+        Some(((y - spread).max(0.0), y + spread))
+    }
+
+    /// Failures expected from time `end` to `t` along with the bounds the failures that occur
+    /// fall within, counting both the uncertainty of the fit and the randomness of the failures
+    pub(crate) fn get_prediction(&self, end: f64, t: f64) -> (f64, Option<(f64, f64)>) {
+        let increment = |params: &[f64]| {
+            self.model.get_mean_value(params, t) - self.model.get_mean_value(params, end)
         };
+        let expected = increment(&self.params);
+        let bounds = self.covariance.as_ref().map(|covariance| {
+            let spread = self.z * (self.get_variance(covariance, &increment) + expected).sqrt();
+            ((expected - spread).max(0.0), expected + spread)
+        });
+        (expected, bounds)
     }
-    fn newton_raphson(self, b: f64, t: f64, c: f64) -> f64 {
-        let mut b = b;
-        println!("{}", c);
-        for _ in 0..1000 {
-            let f_val = self.clone().model(b, t) - c;
-            let f_deriv = self.clone().model_prime(b, t);
-            let new_b = b - f_val / f_deriv;
 
-            if (new_b - b).abs() < 1e-6 {
-                return new_b;
+    /// Variance of a value computed from the parameters by the delta method, from how much it
+    /// changes with the logarithm of each parameter
+    fn get_variance(&self, covariance: &[Vec<f64>], value: &dyn Fn(&[f64]) -> f64) -> f64 {
+        let step = 1e-4;
+        let at = |index: usize, factor: f64| {
+            let mut params = self.params.clone();
+            params[index] *= factor;
+            value(&params)
+        };
+        let gradient: Vec<f64> = (0..self.params.len())
+            .map(|x| (at(x, f64::powf(E, step)) - at(x, f64::powf(E, -step))) / (2.0 * step))
+            .collect();
+        let variance: f64 = covariance
+            .iter()
+            .zip(&gradient)
+            .map(|(row, a)| {
+                row.iter()
+                    .zip(&gradient)
+                    .map(|(x, b)| a * x * b)
+                    .sum::<f64>()
+            })
+            .sum();
+        // Rounding can leave a tiny negative variance where the curve is pinned down
+        variance.max(0.0)
+    }
+
+    /// Failures expected over all time, none for models that never run out of failures
+    pub(crate) fn get_expected_total(&self) -> Option<f64> {
+        let params = &self.params;
+        match self.model {
+            MODELTYPES::SCWIND => Some(params[0] / params[1]),
+            MODELTYPES::GO | MODELTYPES::YAMADA | MODELTYPES::INFLECTION | MODELTYPES::WEIBULL => {
+                Some(params[0])
             }
-            b = new_b;
+            MODELTYPES::MUSA | MODELTYPES::CROW => None,
+        }
+    }
+
+    /// Chance of no failures in the `mission` after time `t`
+    pub(crate) fn get_reliability(&self, t: f64, mission: f64) -> f64 {
+        f64::powf(
+            E,
+            -(self.get_mean_value(t + mission) - self.get_mean_value(t)),
+        )
+    }
+
+    /// Time after `t` until the failure intensity falls to `target`, none when it doesn't
+    /// within a billion units of exposure
+    pub(crate) fn get_time_to_target(&self, t: f64, target: f64) -> Result<Option<f64>, RootError> {
+        let gap = |x: f64| target - self.get_intensity(t + x);
+        let gap_prime = |x: f64| {
+            let h = 1e-4 * (t + x).max(1.0);
+            (self.get_intensity(t + x - h) - self.get_intensity(t + x + h)) / (2.0 * h)
+        };
+        match fit::bracket(&gap, TARGET_LIMIT)? {
+            (low, high) if low == high && low > 0.0 => Ok(None),
+            (low, high) if low == high => Ok(Some(0.0)),
+            (low, high) => Ok(Some(fit::find_root(&gap, &gap_prime, low, high)?.x)),
         }
-        return b;
     }
 }
@@ -0,0 +1,95 @@
+use std::collections::{BTreeSet, HashMap};
+
+use clap::ValueEnum;
+
+use super::log::Log;
+
+/// What the values of a dimension are ranked by
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TopBy {
+    Hits,
+    Bytes,
+    Errors,
+}
+
+impl TopBy {
+    /// Amount a log adds to the value it belongs to, sizes are counted in whole bytes
+    pub(crate) fn get_weight(self, log: &Log) -> u64 {
+        match self {
+            TopBy::Hits => 1,
+            TopBy::Bytes => (log.size * 1000.0).round() as u64,
+            TopBy::Errors => (log.status_code.0 >= 400) as u64,
+        }
+    }
+}
+
+/// Counted value, the true count is at most `error` below `count`
+#[derive(Clone, Debug)]
+pub(crate) struct TopEntry {
+    pub(crate) key: String,
+    pub(crate) count: u64,
+    pub(crate) error: u64,
+}
+
+/// Space saving heavy hitters that keep at most `capacity` counters.
+/// Counts are exact until more distinct values than counters are seen.
+pub(crate) struct SpaceSaving {
+    capacity: usize,
+    /// Count and largest possible overestimate of each tracked value
+    counters: HashMap<String, (u64, u64)>,
+    /// Tracked values ordered by count so the smallest can be replaced
+    order: BTreeSet<(u64, String)>,
+    /// Sum of every weight added, including values that were replaced
+    pub(crate) total: u64,
+    /// Whether a counter was ever replaced, making the counts estimates
+    pub(crate) approximate: bool,
+}
+
+impl SpaceSaving {
+    pub(crate) fn new(capacity: usize) -> SpaceSaving {
+        SpaceSaving {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+            order: BTreeSet::new(),
+            total: 0,
+            approximate: false,
+        }
+    }
+
+    pub(crate) fn add(&mut self, key: String, weight: u64) {
+        if weight == 0 {
+            return;
+        }
+        self.total += weight;
+        if let Some((count, _)) = self.counters.get_mut(&key) {
+            self.order.remove(&(*count, key.clone()));
+            *count += weight;
+            self.order.insert((*count, key));
+            return;
+        }
+        let mut error = 0;
+        if self.counters.len() >= self.capacity {
+            // The least counted value makes room and its count becomes the new value's error
+            let (count, smallest) = self.order.pop_first().unwrap();
+            self.counters.remove(&smallest);
+            error = count;
+            self.approximate = true;
+        }
+        self.counters.insert(key.clone(), (error + weight, error));
+        self.order.insert((error + weight, key));
+    }
+
+    /// The `count` largest values, largest first
+    pub(crate) fn get_top(&self, count: usize) -> Vec<TopEntry> {
+        self.order
+            .iter()
+            .rev()
+            .take(count)
+            .map(|(count, key)| TopEntry {
+                key: key.clone(),
+                count: *count,
+                error: self.counters[key].1,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, read_to_string, write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Bits of the hash that pick a register, giving 2^14 registers and about 0.8% error
+const PRECISION: u32 = 14;
+/// Distinct values kept exactly before switching to a sketch
+const EXACT_LIMIT: usize = 4096;
+
+/// Hash that stays the same across runs so saved counters can be merged
+fn hash(value: &str) -> u64 {
+    // FNV-1a followed by the splitmix64 finalizer to spread the bits
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in value.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+/// Counts distinct values, exactly while there are few of them and with a HyperLogLog sketch after
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum UniqueCounter {
+    Exact(HashSet<u64>),
+    /// Largest run of leading zeros seen by each register
+    Sketch(Vec<u8>),
+}
+
+impl Default for UniqueCounter {
+    fn default() -> UniqueCounter {
+        UniqueCounter::Exact(HashSet::new())
+    }
+}
+
+impl UniqueCounter {
+    pub(crate) fn add(&mut self, value: &str) {
+        self.add_hash(hash(value));
+    }
+
+    fn add_hash(&mut self, hash: u64) {
+        match self {
+            UniqueCounter::Exact(hashes) => {
+                hashes.insert(hash);
+                if hashes.len() > EXACT_LIMIT {
+                    self.make_sketch();
+                }
+            }
+            UniqueCounter::Sketch(registers) => {
+                let register = (hash >> (64 - PRECISION)) as usize;
+                // The sentinel bit keeps the run finite when the remaining bits are all zero
+                let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+                registers[register] = registers[register].max(rank);
+            }
+        }
+    }
+
+    fn make_sketch(&mut self) {
+        if let UniqueCounter::Exact(hashes) = self {
+            let hashes = std::mem::take(hashes);
+            *self = UniqueCounter::Sketch(vec![0; 1 << PRECISION]);
+            for hash in hashes {
+                self.add_hash(hash);
+            }
+        }
+    }
+
+    /// Adds the values counted by another counter, such as another bucket or an earlier run
+    pub(crate) fn merge(&mut self, other: &UniqueCounter) {
+        match other {
+            UniqueCounter::Exact(hashes) => {
+                for hash in hashes {
+                    self.add_hash(*hash);
+                }
+            }
+            UniqueCounter::Sketch(other_registers) => {
+                self.make_sketch();
+                if let UniqueCounter::Sketch(registers) = self {
+                    for (register, other) in registers.iter_mut().zip(other_registers) {
+                        *register = (*register).max(*other);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads a counter saved by an earlier run, starting empty when there is none yet
+    pub(crate) fn load(path: &Path) -> UniqueCounter {
+        match read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).expect("unable to read the counter"),
+            Err(_) => UniqueCounter::default(),
+        }
+    }
+
+    /// Writes the counter so a later run can merge its own values into it
+    pub(crate) fn save(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).expect("unable to create the counter directory");
+        }
+        let contents = serde_json::to_string(self).expect("unable to write json");
+        write(path, contents).expect("unable to write the counter");
+    }
+
+    /// Number of distinct values, estimated once the counter holds a sketch
+    pub(crate) fn count(&self) -> usize {
+        match self {
+            UniqueCounter::Exact(hashes) => hashes.len(),
+            UniqueCounter::Sketch(registers) => {
+                let m = registers.len() as f64;
+                let alpha = 0.7213 / (1.0 + 1.079 / m);
+                let sum: f64 = registers.iter().map(|x| 2f64.powi(-(*x as i32))).sum();
+                let estimate = alpha * m * m / sum;
+                let zeros = registers.iter().filter(|x| **x == 0).count();
+                // Linear counting is more accurate while many registers are still empty
+                match estimate <= 2.5 * m && zeros > 0 {
+                    true => (m * (m / zeros as f64).ln()).round() as usize,
+                    false => estimate.round() as usize,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_values(values: impl Iterator<Item = usize>) -> UniqueCounter {
+        let mut counter = UniqueCounter::default();
+        for value in values {
+            counter.add(&format!("10.0.{}.{}", value / 256, value % 256));
+        }
+        counter
+    }
+
+    #[test]
+    fn counts_exactly_below_the_limit() {
+        let counter = count_values((0..1000).chain(0..1000));
+        assert!(matches!(counter, UniqueCounter::Exact(_)));
+        assert_eq!(counter.count(), 1000);
+    }
+
+    #[test]
+    fn estimates_with_a_sketch_past_the_limit() {
+        for distinct in [EXACT_LIMIT + 1, 20_000, 200_000] {
+            let counter = count_values(0..distinct);
+            assert!(matches!(counter, UniqueCounter::Sketch(_)));
+            let error = (counter.count() as f64 - distinct as f64).abs() / distinct as f64;
+            assert!(error < 0.02, "{distinct} counted as {}", counter.count());
+        }
+    }
+
+    #[test]
+    fn merging_matches_counting_the_union() {
+        let union = count_values((0..50_000).chain(100_000..100_500)).count();
+        // Exact into sketch
+        let mut sketch = count_values(0..50_000);
+        sketch.merge(&count_values((49_000..50_000).chain(100_000..100_500)));
+        assert_eq!(sketch.count(), union);
+        // Sketch into exact
+        let mut exact = count_values((49_000..50_000).chain(100_000..100_500));
+        exact.merge(&count_values(0..50_000));
+        assert_eq!(exact.count(), union);
+        // Exact into exact stays exact
+        let mut exact = count_values(0..100);
+        exact.merge(&count_values(50..150));
+        assert!(matches!(exact, UniqueCounter::Exact(_)));
+        assert_eq!(exact.count(), 150);
+    }
+
+    #[test]
+    fn saved_counters_load_the_same() {
+        let path = std::env::temp_dir().join(format!("unique-{}.json", std::process::id()));
+        for counter in [count_values(0..100), count_values(0..10_000)] {
+            counter.save(&path);
+            assert_eq!(UniqueCounter::load(&path).count(), counter.count());
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(UniqueCounter::load(&path).count(), 0);
+    }
+}